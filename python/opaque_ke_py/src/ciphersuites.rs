@@ -1,10 +1,11 @@
 use pyo3::prelude::*;
 use pyo3::types::PyModule;
 
+use crate::errors::invalid_state_err;
 use crate::py_utils;
 use crate::suite::{
     ML_KEM_768_RISTRETTO255_SHA512, P256_SHA256, P384_SHA384, P521_SHA512, RISTRETTO255_SHA512,
-    SuiteId,
+    SuiteId, parse_suite,
 };
 
 #[pyfunction]
@@ -12,6 +13,33 @@ fn available() -> Vec<&'static str> {
     SuiteId::available()
 }
 
+/// Walks [`SuiteId::preference_order`] and returns the strongest suite both
+/// sides support, so a client and server can agree on a suite without
+/// either one hard-coding it.
+#[pyfunction]
+fn negotiate(client_supported: Vec<String>, server_supported: Vec<String>) -> PyResult<&'static str> {
+    let client: Vec<SuiteId> = client_supported
+        .iter()
+        .filter_map(|s| parse_suite(Some(s)).ok())
+        .collect();
+    let server: Vec<SuiteId> = server_supported
+        .iter()
+        .filter_map(|s| parse_suite(Some(s)).ok())
+        .collect();
+
+    SuiteId::preference_order()
+        .iter()
+        .find(|suite| client.contains(suite) && server.contains(suite))
+        .map(|suite| suite.as_str())
+        .ok_or_else(|| {
+            invalid_state_err(&format!(
+                "no common cipher suite: client supports [{}], server supports [{}]",
+                client_supported.join(", "),
+                server_supported.join(", ")
+            ))
+        })
+}
+
 pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
     let module = py_utils::new_submodule(py, parent, "ciphersuites")?;
     module.add("RISTRETTO255_SHA512", RISTRETTO255_SHA512)?;
@@ -23,6 +51,7 @@ pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
         ML_KEM_768_RISTRETTO255_SHA512,
     )?;
     module.add_function(wrap_pyfunction!(available, &module)?)?;
+    module.add_function(wrap_pyfunction!(negotiate, &module)?)?;
     py_utils::add_submodule(py, parent, "ciphersuites", &module)?;
     Ok(())
 }
@@ -5,6 +5,7 @@ use opaque_ke::{
 };
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
+use zeroize::Zeroizing;
 
 use crate::errors::{invalid_login_err, invalid_state_err, to_py_err};
 use crate::py_utils;
@@ -16,7 +17,7 @@ use crate::types::{
     ClientLoginFinishParameters as PyClientLoginFinishParameters, ClientLoginState,
     ClientLoginStateInner,
     ClientRegistrationFinishParameters as PyClientRegistrationFinishParameters,
-    ClientRegistrationState, ClientRegistrationStateInner,
+    ClientRegistrationState, ClientRegistrationStateInner, SecretBytes,
 };
 
 #[pyclass(unsendable)]
@@ -38,6 +39,7 @@ impl OpaqueClient {
         py: Python<'_>,
         password: Vec<u8>,
     ) -> PyResult<(Py<PyBytes>, ClientRegistrationState)> {
+        let password = Zeroizing::new(password);
         let mut rng = OsRng;
         match self.suite {
             SuiteId::Ristretto255Sha512 => {
@@ -108,7 +110,8 @@ impl OpaqueClient {
         password: Vec<u8>,
         response: Vec<u8>,
         params: Option<PyRef<'_, PyClientRegistrationFinishParameters>>,
-    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>)> {
+    ) -> PyResult<(Py<PyBytes>, SecretBytes)> {
+        let password = Zeroizing::new(password);
         let state_suite = state.suite_id();
         if state_suite != self.suite {
             return Err(invalid_state_err(
@@ -145,7 +148,7 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::to_secret_bytes(export_key),
                 ))
             }
             SuiteId::P256Sha256 => {
@@ -159,7 +162,7 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::to_secret_bytes(export_key),
                 ))
             }
             SuiteId::P384Sha384 => {
@@ -173,7 +176,7 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::to_secret_bytes(export_key),
                 ))
             }
             SuiteId::P521Sha512 => {
@@ -187,7 +190,7 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::to_secret_bytes(export_key),
                 ))
             }
             SuiteId::MlKem768Ristretto255Sha512 => {
@@ -202,7 +205,7 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::to_secret_bytes(export_key),
                 ))
             }
         }
@@ -213,6 +216,7 @@ impl OpaqueClient {
         py: Python<'_>,
         password: Vec<u8>,
     ) -> PyResult<(Py<PyBytes>, ClientLoginState)> {
+        let password = Zeroizing::new(password);
         let mut rng = OsRng;
         match self.suite {
             SuiteId::Ristretto255Sha512 => {
@@ -275,6 +279,10 @@ impl OpaqueClient {
         }
     }
 
+    /// `legacy_bytes`, if set to `True`, returns `session_key`/`export_key`
+    /// as plain `bytes` instead of the default redacting `SecretBytes` —
+    /// only for callers migrating existing code that logs or serializes
+    /// these values directly; new code should leave it unset.
     fn finish_login(
         &self,
         py: Python<'_>,
@@ -282,7 +290,10 @@ impl OpaqueClient {
         password: Vec<u8>,
         response: Vec<u8>,
         params: Option<PyRef<'_, PyClientLoginFinishParameters>>,
-    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>, Py<PyBytes>, Py<PyBytes>)> {
+        legacy_bytes: Option<bool>,
+    ) -> PyResult<(Py<PyBytes>, Py<PyAny>, Py<PyAny>, Py<PyBytes>)> {
+        let legacy_bytes = legacy_bytes.unwrap_or(false);
+        let password = Zeroizing::new(password);
         let state_suite = state.suite_id();
         if state_suite != self.suite {
             return Err(invalid_state_err(
@@ -332,8 +343,8 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &session_key),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                    py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                     py_utils::to_pybytes(py, &server_s_pk),
                 ))
             }
@@ -355,8 +366,8 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &session_key),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                    py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                     py_utils::to_pybytes(py, &server_s_pk),
                 ))
             }
@@ -378,8 +389,8 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &session_key),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                    py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                     py_utils::to_pybytes(py, &server_s_pk),
                 ))
             }
@@ -401,8 +412,8 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &session_key),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                    py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                     py_utils::to_pybytes(py, &server_s_pk),
                 ))
             }
@@ -425,8 +436,8 @@ impl OpaqueClient {
                 let export_key = result.export_key.to_vec();
                 Ok((
                     py_utils::to_pybytes(py, &message),
-                    py_utils::to_pybytes(py, &session_key),
-                    py_utils::to_pybytes(py, &export_key),
+                    py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                    py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                     py_utils::to_pybytes(py, &server_s_pk),
                 ))
             }
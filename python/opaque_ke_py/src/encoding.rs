@@ -7,6 +7,7 @@ use pyo3::types::{PyBytes, PyModule};
 
 use crate::errors::serialization_err;
 use crate::py_utils;
+use crate::suite::{SuiteId, parse_suite};
 
 #[pyfunction]
 fn encode_b64(data: Vec<u8>) -> PyResult<String> {
@@ -26,10 +27,165 @@ fn decode_b64(py: Python<'_>, text: &str) -> PyResult<Py<PyBytes>> {
     Ok(PyBytes::new_bound(py, &decoded).into())
 }
 
+const ARMOR_LINE_WIDTH: usize = 64;
+const CRC24_INIT: u32 = 0x00B7_04CE;
+const CRC24_POLY: u32 = 0x0186_4CFB;
+
+/// CRC-24 checksum (as used by OpenPGP ASCII armor) over the raw,
+/// pre-encoding payload bytes.
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+fn wrap_base64(encoded: &str) -> String {
+    encoded
+        .as_bytes()
+        .chunks(ARMOR_LINE_WIDTH)
+        .map(|chunk| std::str::from_utf8(chunk).expect("base64 output is ASCII"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Wraps `data` in a labeled, PEM-style ASCII armor block so OPAQUE protocol
+/// messages can be safely copy-pasted over text channels that bare base64
+/// doesn't survive (mail clients, chat apps that eat whitespace, etc.).
+#[pyfunction]
+fn armor(data: Vec<u8>, label: &str) -> String {
+    let label = label.trim().to_uppercase();
+    let body = wrap_base64(&URL_SAFE_NO_PAD.encode(&data));
+    let checksum = URL_SAFE_NO_PAD.encode(crc24(&data).to_be_bytes()[1..].to_vec());
+    format!(
+        "-----BEGIN OPAQUE {label}-----\n\n{body}\n={checksum}\n-----END OPAQUE {label}-----"
+    )
+}
+
+/// Reverses [`armor`]: strips surrounding whitespace, confirms the BEGIN/END
+/// labels match, verifies the optional `=XXXX` checksum line when present,
+/// and returns the decoded payload together with its label.
+#[pyfunction]
+fn dearmor(py: Python<'_>, text: &str) -> PyResult<(Py<PyBytes>, String)> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let begin_label = lines
+        .first()
+        .and_then(|line| line.strip_prefix("-----BEGIN OPAQUE "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| serialization_err("missing or malformed armor BEGIN line"))?;
+    let end_label = lines
+        .last()
+        .and_then(|line| line.strip_prefix("-----END OPAQUE "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| serialization_err("missing or malformed armor END line"))?;
+    if begin_label != end_label {
+        return Err(serialization_err(
+            "armor BEGIN and END labels do not match",
+        ));
+    }
+
+    let mut body_lines = &lines[1..lines.len() - 1];
+    let mut checksum = None;
+    if let Some(last) = body_lines.last() {
+        if let Some(encoded) = last.strip_prefix('=') {
+            checksum = Some(encoded.to_string());
+            body_lines = &body_lines[..body_lines.len() - 1];
+        }
+    }
+
+    let joined = body_lines.concat();
+    let decoded = URL_SAFE_NO_PAD
+        .decode(&joined)
+        .map_err(|err| serialization_err(&err.to_string()))?;
+
+    if let Some(checksum) = checksum {
+        let expected = URL_SAFE_NO_PAD
+            .decode(&checksum)
+            .map_err(|err| serialization_err(&err.to_string()))?;
+        let actual = crc24(&decoded).to_be_bytes()[1..].to_vec();
+        if expected != actual {
+            return Err(serialization_err(
+                "armor checksum does not match payload; block is corrupted",
+            ));
+        }
+    }
+
+    Ok((PyBytes::new_bound(py, &decoded).into(), begin_label.to_string()))
+}
+
+const MESSAGE_ENVELOPE_VERSION: u8 = 1;
+
+/// Prepends a 1-byte version and a 1-byte [`SuiteId::tag`] to a protocol
+/// message's `serialize()` output, so the cipher suite travels with the
+/// bytes instead of needing to be passed out-of-band alongside them.
+///
+/// This header is intentionally tiny and has no magic byte sequence, unlike
+/// [`crate::py_utils::encode_tagged`]'s `"OK"`-prefixed envelope. That makes
+/// [`try_unwrap`] a probabilistic guess, not a verified format check: any
+/// *untagged* payload that happens to start with `MESSAGE_ENVELOPE_VERSION`
+/// followed by a byte in `SuiteId`'s tag range will be silently (and
+/// wrongly) unwrapped. See `try_unwrap` for where this matters.
+#[pyfunction]
+fn wrap(py: Python<'_>, suite: &str, data: Vec<u8>) -> PyResult<Py<PyBytes>> {
+    let suite = parse_suite(Some(suite))?;
+    let mut out = Vec::with_capacity(2 + data.len());
+    out.push(MESSAGE_ENVELOPE_VERSION);
+    out.push(suite.tag());
+    out.extend_from_slice(&data);
+    Ok(PyBytes::new_bound(py, &out).into())
+}
+
+/// Reverses [`wrap`], returning the suite name and the inner payload.
+#[pyfunction]
+fn unwrap(py: Python<'_>, data: Vec<u8>) -> PyResult<(String, Py<PyBytes>)> {
+    let (suite, payload) = try_unwrap(&data)
+        .ok_or_else(|| serialization_err("not a recognized suite-tagged message envelope"))?;
+    Ok((suite.as_str().to_string(), PyBytes::new_bound(py, payload).into()))
+}
+
+/// Non-throwing variant of [`unwrap`] for call sites that want to probe a
+/// message for an embedded suite tag and fall back when one isn't present.
+///
+/// **This is a heuristic, not a verified format check.** The envelope has no
+/// magic bytes, so a two-byte prefix match is all that distinguishes a
+/// wrapped message from an unwrapped one: any payload — tagged or not —
+/// whose first byte is `MESSAGE_ENVELOPE_VERSION` (currently `1`) and whose
+/// second byte is a valid `SuiteId` tag will be treated as wrapped, its
+/// first two bytes stripped, and the remainder deserialized under the
+/// guessed suite. For most OPAQUE protocol messages this collision is
+/// unlikely but not impossible, and it fails closed into a confusing
+/// deserialization error rather than a clear "ambiguous input" one. Callers
+/// who receive raw, never-wrapped input from a source they don't fully
+/// control (rather than input produced by [`wrap`]) should pass an explicit
+/// `suite` instead of relying on this fallback — see
+/// `registration.server_finish_registration`'s `suite` parameter.
+pub(crate) fn try_unwrap(data: &[u8]) -> Option<(SuiteId, &[u8])> {
+    if data.len() < 2 || data[0] != MESSAGE_ENVELOPE_VERSION {
+        return None;
+    }
+    SuiteId::from_tag(data[1]).map(|suite| (suite, &data[2..]))
+}
+
 pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
     let module = py_utils::new_submodule(py, parent, "encoding")?;
     module.add_function(wrap_pyfunction!(encode_b64, &module)?)?;
     module.add_function(wrap_pyfunction!(decode_b64, &module)?)?;
+    module.add_function(wrap_pyfunction!(armor, &module)?)?;
+    module.add_function(wrap_pyfunction!(dearmor, &module)?)?;
+    module.add_function(wrap_pyfunction!(wrap, &module)?)?;
+    module.add_function(wrap_pyfunction!(unwrap, &module)?)?;
     py_utils::add_submodule(py, parent, "encoding", &module)?;
     Ok(())
 }
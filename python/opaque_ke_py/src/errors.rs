@@ -1,13 +1,39 @@
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::PyModule;
 
+create_exception!(errors, InvalidStateError, PyRuntimeError);
+create_exception!(errors, InvalidLoginError, PyRuntimeError);
+create_exception!(errors, SerializationError, PyValueError);
+create_exception!(errors, ThrottledError, PyRuntimeError);
+
 pub(crate) fn to_py_err<E: std::fmt::Display>(err: E) -> PyErr {
     PyErr::new::<PyRuntimeError, _>(err.to_string())
 }
 
+pub(crate) fn invalid_state_err(message: &str) -> PyErr {
+    PyErr::new::<InvalidStateError, _>(message.to_string())
+}
+
+pub(crate) fn invalid_login_err(message: &str) -> PyErr {
+    PyErr::new::<InvalidLoginError, _>(message.to_string())
+}
+
+pub(crate) fn serialization_err(message: &str) -> PyErr {
+    PyErr::new::<SerializationError, _>(message.to_string())
+}
+
+pub(crate) fn throttled_err(message: &str) -> PyErr {
+    PyErr::new::<ThrottledError, _>(message.to_string())
+}
+
 pub fn register(py: Python<'_>, parent: &PyModule) -> PyResult<()> {
     let module = PyModule::new(py, "errors")?;
+    module.add("InvalidStateError", py.get_type::<InvalidStateError>())?;
+    module.add("InvalidLoginError", py.get_type::<InvalidLoginError>())?;
+    module.add("SerializationError", py.get_type::<SerializationError>())?;
+    module.add("ThrottledError", py.get_type::<ThrottledError>())?;
     parent.add_submodule(module)?;
     Ok(())
 }
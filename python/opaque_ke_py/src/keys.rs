@@ -0,0 +1,66 @@
+use hmac::{Hmac, Mac};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyModule};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::errors::serialization_err;
+use crate::py_utils;
+use crate::suite::{SuiteId, parse_suite};
+
+/// HKDF-Expand (RFC 5869 section 2.3) keyed by an already-extracted
+/// pseudorandom key. `export_key` is high-entropy on its own, so callers
+/// skip HKDF-Extract and expand straight from it.
+fn hkdf_expand<D>(prk: &[u8], info: &[u8], length: usize) -> PyResult<Vec<u8>>
+where
+    D: Digest + Clone,
+    Hmac<D>: Mac,
+{
+    let mut okm = Vec::with_capacity(length);
+    let mut block = Vec::new();
+    let mut counter: u8 = 0;
+    while okm.len() < length {
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| serialization_err("HKDF-Expand counter overflow"))?;
+        let mut mac = <Hmac<D> as Mac>::new_from_slice(prk)
+            .map_err(|err| serialization_err(&err.to_string()))?;
+        mac.update(&block);
+        mac.update(info);
+        mac.update(&[counter]);
+        block = mac.finalize().into_bytes().to_vec();
+        okm.extend_from_slice(&block);
+    }
+    okm.truncate(length);
+    Ok(okm)
+}
+
+/// Expands an OPAQUE `export_key` into `length` bytes of domain-separated
+/// key material via HKDF-Expand, using the hash tied to `suite`. Lets
+/// callers deterministically mint an encryption key, a MAC key, a backup
+/// key, etc. from a single login without reimplementing HKDF in Python.
+#[pyfunction]
+fn derive(py: Python<'_>, export_key: Vec<u8>, info: Vec<u8>, length: usize, suite: &str) -> PyResult<Py<PyBytes>> {
+    let suite = parse_suite(Some(suite))?;
+    let hash_len = suite.hash_output_len();
+    if length > 255 * hash_len {
+        return Err(serialization_err(&format!(
+            "requested length {length} exceeds HKDF-Expand maximum of {} bytes for this suite",
+            255 * hash_len
+        )));
+    }
+    let okm = match suite {
+        SuiteId::P256Sha256 => hkdf_expand::<Sha256>(&export_key, &info, length)?,
+        SuiteId::P384Sha384 => hkdf_expand::<Sha384>(&export_key, &info, length)?,
+        SuiteId::Ristretto255Sha512
+        | SuiteId::P521Sha512
+        | SuiteId::MlKem768Ristretto255Sha512 => hkdf_expand::<Sha512>(&export_key, &info, length)?,
+    };
+    Ok(py_utils::to_pybytes(py, &okm))
+}
+
+pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let module = py_utils::new_submodule(py, parent, "keys")?;
+    module.add_function(wrap_pyfunction!(derive, &module)?)?;
+    py_utils::add_submodule(py, parent, "keys", &module)?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use generic_array::typenum::Unsigned;
+use generic_array::{ArrayLength, GenericArray};
+use opaque_ke::argon2::Argon2;
+use opaque_ke::errors::InternalError;
+use opaque_ke::ksf::Ksf;
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Sha256, Sha512};
+
+#[derive(Clone, Copy)]
+pub(crate) enum Pbkdf2Hash {
+    Sha256,
+    Sha512,
+}
+
+/// Crate-local dispatch over every key-stretching backend a `KeyStretching`
+/// value can select from Python. `CipherSuite::Ksf` is fixed to this enum for
+/// every suite so a single Python-facing type can carry any backend.
+#[derive(Clone)]
+pub(crate) enum AnyKsf {
+    Argon2(Argon2<'static>),
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    Pbkdf2 { hash: Pbkdf2Hash, iterations: u32 },
+    Identity,
+}
+
+impl Default for AnyKsf {
+    fn default() -> Self {
+        AnyKsf::Argon2(Argon2::default())
+    }
+}
+
+impl Ksf for AnyKsf {
+    fn hash<L: ArrayLength<u8>>(
+        &self,
+        input: GenericArray<u8, L>,
+    ) -> Result<GenericArray<u8, L>, InternalError> {
+        match self {
+            AnyKsf::Argon2(argon2) => argon2.hash(input),
+            AnyKsf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(*log_n, *r, *p, L::to_usize())
+                    .map_err(|_| InternalError::KsfError)?;
+                let mut output = GenericArray::<u8, L>::default();
+                scrypt::scrypt(&input, &[], &params, &mut output)
+                    .map_err(|_| InternalError::KsfError)?;
+                Ok(output)
+            }
+            AnyKsf::Pbkdf2 { hash, iterations } => {
+                let mut output = GenericArray::<u8, L>::default();
+                match hash {
+                    Pbkdf2Hash::Sha256 => pbkdf2_hmac::<Sha256>(&input, &[], *iterations, &mut output),
+                    Pbkdf2Hash::Sha512 => pbkdf2_hmac::<Sha512>(&input, &[], *iterations, &mut output),
+                }
+                Ok(output)
+            }
+            AnyKsf::Identity => {
+                let mut output = GenericArray::<u8, L>::default();
+                let len = input.len().min(output.len());
+                output[..len].copy_from_slice(&input[..len]);
+                Ok(output)
+            }
+        }
+    }
+}
@@ -7,11 +7,18 @@ mod ciphersuites;
 mod client;
 mod encoding;
 mod errors;
+mod keys;
+mod ksf;
 mod login;
+mod login_sasl;
 mod py_utils;
 mod registration;
+mod rng;
+mod sasl;
 mod server;
+mod session;
 mod suite;
+mod throttle;
 mod types;
 
 #[pymodule]
@@ -27,5 +34,9 @@ fn opaque_ke(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     encoding::register(py, m)?;
     client::register(py, m)?;
     server::register(py, m)?;
+    sasl::register(py, m)?;
+    session::register(py, m)?;
+    throttle::register(py, m)?;
+    keys::register(py, m)?;
     Ok(())
 }
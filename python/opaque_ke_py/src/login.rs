@@ -1,13 +1,14 @@
-use opaque_ke::rand::rngs::OsRng;
 use opaque_ke::{
     ClientLogin, ClientLoginFinishParameters, CredentialFinalization, CredentialRequest,
     CredentialResponse, ServerLogin, ServerLoginParameters,
 };
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
+use zeroize::Zeroizing;
 
-use crate::errors::{invalid_login_err, invalid_state_err, to_py_err};
+use crate::errors::{invalid_login_err, invalid_state_err, throttled_err, to_py_err};
 use crate::py_utils;
+use crate::rng::make_rng;
 use crate::suite::{parse_suite, Ristretto255Sha512, SuiteId};
 use crate::suite::MlKem768Ristretto255Sha512;
 use crate::suite::P256Sha256;
@@ -30,76 +31,62 @@ fn ensure_suite(expected: SuiteId, actual: SuiteId, label: &str) -> PyResult<()>
     }
 }
 
+/// `seed`, if given, must be exactly 32 bytes and switches this call's
+/// nonce/blind generation from `OsRng` to a `ChaCha20Rng` seeded from it, so
+/// a full protocol transcript can be reproduced byte-for-byte. This is
+/// strictly for known-answer test vectors — never set it outside a test
+/// harness.
+///
+/// `rng`, if given, is a callable of shape `(nbytes: int) -> bytes` used in
+/// place of both `OsRng` and `seed` — for an external entropy source (an
+/// HSM, a FIPS-validated module) that Rust can't construct directly. Takes
+/// precedence over `seed` if both are set.
 #[pyfunction(name = "start_login")]
-#[pyo3(signature = (password, suite=None))]
+#[pyo3(signature = (password, suite=None, seed=None, rng=None))]
 fn client_start_login(
     py: Python<'_>,
     password: Vec<u8>,
     suite: Option<&str>,
+    seed: Option<Vec<u8>>,
+    rng: Option<Py<PyAny>>,
 ) -> PyResult<(Py<PyBytes>, ClientLoginState)> {
+    let password = Zeroizing::new(password);
     let suite = parse_suite(suite)?;
-    let mut rng = OsRng;
-    match suite {
-        SuiteId::Ristretto255Sha512 => {
-            let result = ClientLogin::<Ristretto255Sha512>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientLoginState {
-                    inner: ClientLoginStateInner::Ristretto255Sha512(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P256Sha256 => {
-            let result = ClientLogin::<P256Sha256>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientLoginState {
-                    inner: ClientLoginStateInner::P256Sha256(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P384Sha384 => {
-            let result = ClientLogin::<P384Sha384>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientLoginState {
-                    inner: ClientLoginStateInner::P384Sha384(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P521Sha512 => {
-            let result = ClientLogin::<P521Sha512>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientLoginState {
-                    inner: ClientLoginStateInner::P521Sha512(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::MlKem768Ristretto255Sha512 => {
-            let result = ClientLogin::<MlKem768Ristretto255Sha512>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientLoginState {
-                    inner: ClientLoginStateInner::MlKem768Ristretto255Sha512(Some(result.state)),
-                },
-            ))
-        }
-    }
+    let mut rng = make_rng(seed.as_deref(), rng)?;
+    py_utils::per_suite_dispatch!(
+        suite = suite,
+        py = py,
+        rng = rng,
+        password = password,
+        start = ClientLogin,
+        state_type = ClientLoginState,
+        state_inner = ClientLoginStateInner,
+        after_start = rng.take_error()?,
+        [
+            (SuiteId::Ristretto255Sha512, Ristretto255Sha512, Ristretto255Sha512),
+            (SuiteId::P256Sha256, P256Sha256, P256Sha256),
+            (SuiteId::P384Sha384, P384Sha384, P384Sha384),
+            (SuiteId::P521Sha512, P521Sha512, P521Sha512),
+            (
+                SuiteId::MlKem768Ristretto255Sha512,
+                MlKem768Ristretto255Sha512,
+                MlKem768Ristretto255Sha512
+            ),
+        ]
+    )
 }
 
+/// `legacy_bytes`, if `True`, returns `session_key`/`export_key` as plain
+/// `bytes` instead of the default redacting `SecretBytes` — only for
+/// callers migrating existing code; new code should leave it unset.
+/// `seed`, if given, must be exactly 32 bytes and switches this call's
+/// envelope-opening randomness from `OsRng` to a `ChaCha20Rng` seeded from
+/// it, strictly for known-answer test vectors.
+/// `rng`, if given, is a callable of shape `(nbytes: int) -> bytes` used in
+/// place of both `OsRng` and `seed`, for an external entropy source. Takes
+/// precedence over `seed` if both are set.
 #[pyfunction(name = "finish_login")]
-#[pyo3(signature = (state, password, response, params=None, suite=None))]
+#[pyo3(signature = (state, password, response, params=None, suite=None, legacy_bytes=false, seed=None, rng=None))]
 fn client_finish_login(
     py: Python<'_>,
     mut state: PyRefMut<'_, ClientLoginState>,
@@ -107,7 +94,11 @@ fn client_finish_login(
     response: Vec<u8>,
     params: Option<PyRef<'_, PyClientLoginFinishParameters>>,
     suite: Option<&str>,
-) -> PyResult<(Py<PyBytes>, Py<PyBytes>, Py<PyBytes>, Py<PyBytes>)> {
+    legacy_bytes: bool,
+    seed: Option<Vec<u8>>,
+    rng: Option<Py<PyAny>>,
+) -> PyResult<(Py<PyBytes>, Py<PyAny>, Py<PyAny>, Py<PyBytes>)> {
+    let password = Zeroizing::new(password);
     let state_suite = state.suite_id();
     if let Some(requested) = suite {
         let requested = parse_suite(Some(requested))?;
@@ -136,7 +127,7 @@ fn client_finish_login(
     } else {
         ClientLoginFinishParameters::default()
     };
-    let mut rng = OsRng;
+    let mut rng = make_rng(seed.as_deref(), rng)?;
     match state_suite {
         SuiteId::Ristretto255Sha512 => {
             let state = state.take_ristretto()?;
@@ -146,6 +137,7 @@ fn client_finish_login(
             let result = state
                 .finish(&mut rng, &password, response, finish_params)
                 .map_err(to_py_err)?;
+            rng.take_error()?;
             let server_s_pk = result.server_s_pk.serialize().to_vec();
             if let Some(expected) = expected_server_s_pk {
                 if expected != server_s_pk {
@@ -157,8 +149,8 @@ fn client_finish_login(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &session_key),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                 py_utils::to_pybytes(py, &server_s_pk),
             ))
         }
@@ -169,6 +161,7 @@ fn client_finish_login(
             let result = state
                 .finish(&mut rng, &password, response, finish_params)
                 .map_err(to_py_err)?;
+            rng.take_error()?;
             let server_s_pk = result.server_s_pk.serialize().to_vec();
             if let Some(expected) = expected_server_s_pk {
                 if expected != server_s_pk {
@@ -180,8 +173,8 @@ fn client_finish_login(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &session_key),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                 py_utils::to_pybytes(py, &server_s_pk),
             ))
         }
@@ -192,6 +185,7 @@ fn client_finish_login(
             let result = state
                 .finish(&mut rng, &password, response, finish_params)
                 .map_err(to_py_err)?;
+            rng.take_error()?;
             let server_s_pk = result.server_s_pk.serialize().to_vec();
             if let Some(expected) = expected_server_s_pk {
                 if expected != server_s_pk {
@@ -203,8 +197,8 @@ fn client_finish_login(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &session_key),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                 py_utils::to_pybytes(py, &server_s_pk),
             ))
         }
@@ -215,6 +209,7 @@ fn client_finish_login(
             let result = state
                 .finish(&mut rng, &password, response, finish_params)
                 .map_err(to_py_err)?;
+            rng.take_error()?;
             let server_s_pk = result.server_s_pk.serialize().to_vec();
             if let Some(expected) = expected_server_s_pk {
                 if expected != server_s_pk {
@@ -226,8 +221,8 @@ fn client_finish_login(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &session_key),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                 py_utils::to_pybytes(py, &server_s_pk),
             ))
         }
@@ -239,6 +234,7 @@ fn client_finish_login(
             let result = state
                 .finish(&mut rng, &password, response, finish_params)
                 .map_err(to_py_err)?;
+            rng.take_error()?;
             let server_s_pk = result.server_s_pk.serialize().to_vec();
             if let Some(expected) = expected_server_s_pk {
                 if expected != server_s_pk {
@@ -250,37 +246,63 @@ fn client_finish_login(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &session_key),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::secret_or_bytes(py, session_key, legacy_bytes)?,
+                py_utils::secret_or_bytes(py, export_key, legacy_bytes)?,
                 py_utils::to_pybytes(py, &server_s_pk),
             ))
         }
     }
 }
 
+/// `password_file=None` is the account-enumeration-resistant path: opaque-ke
+/// synthesizes a dummy evaluated credential from `server_setup`'s OPRF seed
+/// and `credential_identifier`, producing a `CredentialResponse` that is
+/// byte-indistinguishable from a real account's. Callers should invoke this
+/// the same way regardless of whether the account exists, and must still
+/// drive the resulting state through `finish_login` so request timing
+/// doesn't leak existence either.
+///
+/// `seed`, if given, must be exactly 32 bytes and switches this call's
+/// nonce/oprf-evaluation randomness from `OsRng` to a `ChaCha20Rng` seeded
+/// from it, strictly for known-answer test vectors.
+///
+/// `rng`, if given, is a callable of shape `(nbytes: int) -> bytes` used in
+/// place of both `OsRng` and `seed`, for an external entropy source. Takes
+/// precedence over `seed` if both are set.
 #[pyfunction(name = "start_login")]
-#[pyo3(signature = (server_setup, password_file, request, credential_identifier, params=None, suite=None))]
-fn server_start_login(
+#[pyo3(signature = (server_setup, password_file, request, credential_identifier, params=None, suite=None, seed=None, rng=None))]
+pub(crate) fn server_start_login(
     py: Python<'_>,
     server_setup: PyRef<'_, ServerSetup>,
-    password_file: PyRef<'_, ServerRegistration>,
+    password_file: Option<PyRef<'_, ServerRegistration>>,
     request: Vec<u8>,
     credential_identifier: Vec<u8>,
     params: Option<PyRef<'_, PyServerLoginParameters>>,
     suite: Option<&str>,
+    seed: Option<Vec<u8>>,
+    rng: Option<Py<PyAny>>,
 ) -> PyResult<(Py<PyBytes>, ServerLoginState)> {
     let setup_suite = server_setup.suite_id();
-    let password_suite = password_file.suite_id();
-    if setup_suite != password_suite {
-        return Err(invalid_state_err(
-            "ServerSetup and ServerRegistration use different cipher suites",
-        ));
+    if let Some(password_file) = &password_file {
+        if setup_suite != password_file.suite_id() {
+            return Err(invalid_state_err(
+                "ServerSetup and ServerRegistration use different cipher suites",
+            ));
+        }
     }
     if let Some(requested) = suite {
         let requested = parse_suite(Some(requested))?;
         ensure_suite(requested, setup_suite, "ServerSetup")?;
     }
-    let mut rng = OsRng;
+    if let Some(throttle) = params.as_ref().and_then(|params| params.throttle()) {
+        if !throttle
+            .borrow_mut(py)
+            .register_attempt(py, credential_identifier.clone())?
+        {
+            return Err(throttled_err("too many login attempts for this identifier"));
+        }
+    }
+    let mut rng = make_rng(seed.as_deref(), rng)?;
     let identifiers = params
         .as_ref()
         .and_then(|params| params.identifiers().cloned());
@@ -299,123 +321,189 @@ fn server_start_login(
     } else {
         ServerLoginParameters::default()
     };
-    match (&server_setup.inner, &password_file.inner) {
-        (ServerSetupInner::Ristretto255Sha512(setup), ServerRegistrationInner::Ristretto255Sha512(reg)) => {
+    match &server_setup.inner {
+        ServerSetupInner::Ristretto255Sha512(setup) => {
+            let record = match &password_file {
+                Some(password_file) => match &password_file.inner {
+                    ServerRegistrationInner::Ristretto255Sha512(reg) => Some(reg.clone()),
+                    _ => {
+                        return Err(invalid_state_err(
+                            "ServerSetup and ServerRegistration use different cipher suites",
+                        ));
+                    }
+                },
+                None => None,
+            };
             let request =
                 CredentialRequest::<Ristretto255Sha512>::deserialize(&request)
                     .map_err(to_py_err)?;
             let result = ServerLogin::<Ristretto255Sha512>::start(
                 &mut rng,
                 setup,
-                Some(reg.clone()),
+                record,
                 request,
                 &credential_identifier,
                 parameters,
             )
             .map_err(to_py_err)?;
+            rng.take_error()?;
             let message = result.message.serialize().to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
                 ServerLoginState {
                     inner: ServerLoginStateInner::Ristretto255Sha512(Some(result.state)),
+                    credential_identifier: Some(credential_identifier.clone()),
                 },
             ))
         }
-        (ServerSetupInner::P256Sha256(setup), ServerRegistrationInner::P256Sha256(reg)) => {
+        ServerSetupInner::P256Sha256(setup) => {
+            let record = match &password_file {
+                Some(password_file) => match &password_file.inner {
+                    ServerRegistrationInner::P256Sha256(reg) => Some(reg.clone()),
+                    _ => {
+                        return Err(invalid_state_err(
+                            "ServerSetup and ServerRegistration use different cipher suites",
+                        ));
+                    }
+                },
+                None => None,
+            };
             let request =
                 CredentialRequest::<P256Sha256>::deserialize(&request).map_err(to_py_err)?;
             let result = ServerLogin::<P256Sha256>::start(
                 &mut rng,
                 setup,
-                Some(reg.clone()),
+                record,
                 request,
                 &credential_identifier,
                 parameters,
             )
             .map_err(to_py_err)?;
+            rng.take_error()?;
             let message = result.message.serialize().to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
                 ServerLoginState {
                     inner: ServerLoginStateInner::P256Sha256(Some(result.state)),
+                    credential_identifier: Some(credential_identifier.clone()),
                 },
             ))
         }
-        (ServerSetupInner::P384Sha384(setup), ServerRegistrationInner::P384Sha384(reg)) => {
+        ServerSetupInner::P384Sha384(setup) => {
+            let record = match &password_file {
+                Some(password_file) => match &password_file.inner {
+                    ServerRegistrationInner::P384Sha384(reg) => Some(reg.clone()),
+                    _ => {
+                        return Err(invalid_state_err(
+                            "ServerSetup and ServerRegistration use different cipher suites",
+                        ));
+                    }
+                },
+                None => None,
+            };
             let request =
                 CredentialRequest::<P384Sha384>::deserialize(&request).map_err(to_py_err)?;
             let result = ServerLogin::<P384Sha384>::start(
                 &mut rng,
                 setup,
-                Some(reg.clone()),
+                record,
                 request,
                 &credential_identifier,
                 parameters,
             )
             .map_err(to_py_err)?;
+            rng.take_error()?;
             let message = result.message.serialize().to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
                 ServerLoginState {
                     inner: ServerLoginStateInner::P384Sha384(Some(result.state)),
+                    credential_identifier: Some(credential_identifier.clone()),
                 },
             ))
         }
-        (ServerSetupInner::P521Sha512(setup), ServerRegistrationInner::P521Sha512(reg)) => {
+        ServerSetupInner::P521Sha512(setup) => {
+            let record = match &password_file {
+                Some(password_file) => match &password_file.inner {
+                    ServerRegistrationInner::P521Sha512(reg) => Some(reg.clone()),
+                    _ => {
+                        return Err(invalid_state_err(
+                            "ServerSetup and ServerRegistration use different cipher suites",
+                        ));
+                    }
+                },
+                None => None,
+            };
             let request =
                 CredentialRequest::<P521Sha512>::deserialize(&request).map_err(to_py_err)?;
             let result = ServerLogin::<P521Sha512>::start(
                 &mut rng,
                 setup,
-                Some(reg.clone()),
+                record,
                 request,
                 &credential_identifier,
                 parameters,
             )
             .map_err(to_py_err)?;
+            rng.take_error()?;
             let message = result.message.serialize().to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
                 ServerLoginState {
                     inner: ServerLoginStateInner::P521Sha512(Some(result.state)),
+                    credential_identifier: Some(credential_identifier.clone()),
                 },
             ))
         }
-        (ServerSetupInner::MlKem768Ristretto255Sha512(setup), ServerRegistrationInner::MlKem768Ristretto255Sha512(reg)) => {
+        ServerSetupInner::MlKem768Ristretto255Sha512(setup) => {
+            let record = match &password_file {
+                Some(password_file) => match &password_file.inner {
+                    ServerRegistrationInner::MlKem768Ristretto255Sha512(reg) => Some(reg.clone()),
+                    _ => {
+                        return Err(invalid_state_err(
+                            "ServerSetup and ServerRegistration use different cipher suites",
+                        ));
+                    }
+                },
+                None => None,
+            };
             let request = CredentialRequest::<MlKem768Ristretto255Sha512>::deserialize(&request)
                 .map_err(to_py_err)?;
             let result = ServerLogin::<MlKem768Ristretto255Sha512>::start(
                 &mut rng,
                 setup,
-                Some(reg.clone()),
+                record,
                 request,
                 &credential_identifier,
                 parameters,
             )
             .map_err(to_py_err)?;
+            rng.take_error()?;
             let message = result.message.serialize().to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
                 ServerLoginState {
                     inner: ServerLoginStateInner::MlKem768Ristretto255Sha512(Some(result.state)),
+                    credential_identifier: Some(credential_identifier.clone()),
                 },
             ))
         }
-        _ => Err(invalid_state_err(
-            "ServerSetup and ServerRegistration use different cipher suites",
-        )),
     }
 }
 
+/// `legacy_bytes`, if `True`, returns `session_key` as plain `bytes` instead
+/// of the default redacting `SecretBytes` — only for callers migrating
+/// existing code; new code should leave it unset.
 #[pyfunction(name = "finish_login")]
-#[pyo3(signature = (state, finalization, params=None, suite=None))]
-fn server_finish_login(
+#[pyo3(signature = (state, finalization, params=None, suite=None, legacy_bytes=false))]
+pub(crate) fn server_finish_login(
     py: Python<'_>,
     mut state: PyRefMut<'_, ServerLoginState>,
     finalization: Vec<u8>,
     params: Option<PyRef<'_, PyServerLoginParameters>>,
     suite: Option<&str>,
-) -> PyResult<Py<PyBytes>> {
+    legacy_bytes: bool,
+) -> PyResult<Py<PyAny>> {
     let state_suite = state.suite_id();
     if let Some(requested) = suite {
         let requested = parse_suite(Some(requested))?;
@@ -439,39 +527,42 @@ fn server_finish_login(
     } else {
         ServerLoginParameters::default()
     };
-    match state_suite {
+    let throttle = params.as_ref().and_then(|params| params.throttle());
+    let credential_identifier = state.credential_identifier().map(|id| id.to_vec());
+
+    // Run the actual finish as a closure, not a bare `match` with `?`, so that
+    // a malformed finalization or a reused state also counts as a throttle
+    // failure below — otherwise sending garbage instead of a wrong password
+    // would dodge the lockout entirely.
+    let outcome: PyResult<Vec<u8>> = (|| match state_suite {
         SuiteId::Ristretto255Sha512 => {
             let state = state.take_ristretto()?;
             let finalization =
                 CredentialFinalization::<Ristretto255Sha512>::deserialize(&finalization)
                     .map_err(to_py_err)?;
             let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-            let session_key = result.session_key.to_vec();
-            Ok(py_utils::to_pybytes(py, &session_key))
+            Ok(result.session_key.to_vec())
         }
         SuiteId::P256Sha256 => {
             let state = state.take_p256()?;
             let finalization = CredentialFinalization::<P256Sha256>::deserialize(&finalization)
                 .map_err(to_py_err)?;
             let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-            let session_key = result.session_key.to_vec();
-            Ok(py_utils::to_pybytes(py, &session_key))
+            Ok(result.session_key.to_vec())
         }
         SuiteId::P384Sha384 => {
             let state = state.take_p384()?;
             let finalization = CredentialFinalization::<P384Sha384>::deserialize(&finalization)
                 .map_err(to_py_err)?;
             let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-            let session_key = result.session_key.to_vec();
-            Ok(py_utils::to_pybytes(py, &session_key))
+            Ok(result.session_key.to_vec())
         }
         SuiteId::P521Sha512 => {
             let state = state.take_p521()?;
             let finalization = CredentialFinalization::<P521Sha512>::deserialize(&finalization)
                 .map_err(to_py_err)?;
             let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-            let session_key = result.session_key.to_vec();
-            Ok(py_utils::to_pybytes(py, &session_key))
+            Ok(result.session_key.to_vec())
         }
         SuiteId::MlKem768Ristretto255Sha512 => {
             let state = state.take_kem()?;
@@ -479,10 +570,22 @@ fn server_finish_login(
                 CredentialFinalization::<MlKem768Ristretto255Sha512>::deserialize(&finalization)
                     .map_err(to_py_err)?;
             let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-            let session_key = result.session_key.to_vec();
-            Ok(py_utils::to_pybytes(py, &session_key))
+            Ok(result.session_key.to_vec())
+        }
+    })();
+
+    if let (Some(throttle), Some(credential_identifier)) = (&throttle, &credential_identifier) {
+        match &outcome {
+            Ok(_) => throttle.borrow_mut(py).record_success(credential_identifier.clone()),
+            // A clock callback raising here is itself noteworthy, but it
+            // must not bury the real reason the login failed.
+            Err(_) => {
+                let _ = throttle.borrow_mut(py).record_failure(py, credential_identifier.clone());
+            }
         }
     }
+
+    py_utils::secret_or_bytes(py, outcome?, legacy_bytes)
 }
 
 pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -498,6 +601,170 @@ pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
     server.add_function(wrap_pyfunction!(server_finish_login, &server)?)?;
     py_utils::add_submodule(py, &module, "server", &server)?;
 
+    crate::login_sasl::register(py, &module)?;
+
     py_utils::add_submodule(py, parent, "login", &module)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registration::{
+        client_finish_registration, client_start_registration, server_finish_registration,
+        server_start_registration,
+    };
+    use crate::types::ServerSetup;
+
+    const SEED: [u8; 32] = [7u8; 32];
+    const PASSWORD: &[u8] = b"correct horse battery staple";
+    const CREDENTIAL_IDENTIFIER: &[u8] = b"alice";
+
+    /// Runs a full register-then-login exchange for `suite` under a fixed
+    /// seed and returns the client's and server's derived session keys, so a
+    /// test can assert both that they agree (protocol correctness) and that
+    /// repeating the run with the same seed reproduces the same bytes
+    /// (determinism).
+    ///
+    /// This is deliberately a self-consistency check, not a replay of the
+    /// published OPAQUE/RFC 9807 known-answer vectors: this sandbox has no
+    /// network access to fetch them, and `ml_kem_768_ristretto255_sha512` is
+    /// a hybrid extension this crate defines itself, so no RFC vectors exist
+    /// for it regardless. If real upstream vectors become available, this
+    /// should be extended to assert the literal registration/KE1/KE2/KE3 and
+    /// key bytes against them per suite, rather than only internal
+    /// agreement.
+    fn seeded_login_session_keys(py: Python<'_>, suite: &str) -> (Vec<u8>, Vec<u8>) {
+        let server_setup =
+            Py::new(py, ServerSetup::new(Some(suite), Some(SEED.to_vec())).unwrap()).unwrap();
+
+        let (reg_request, reg_state) =
+            client_start_registration(py, PASSWORD.to_vec(), Some(suite)).unwrap();
+        let reg_state = Py::new(py, reg_state).unwrap();
+        let reg_response = server_start_registration(
+            py,
+            server_setup.borrow(py),
+            reg_request.bind(py).as_bytes().to_vec(),
+            CREDENTIAL_IDENTIFIER.to_vec(),
+            None,
+        )
+        .unwrap();
+        let (upload, _export_key) = client_finish_registration(
+            py,
+            reg_state.borrow_mut(py),
+            PASSWORD.to_vec(),
+            reg_response.bind(py).as_bytes().to_vec(),
+            None,
+            None,
+        )
+        .unwrap();
+        let password_file =
+            server_finish_registration(upload.bind(py).as_bytes().to_vec(), Some(suite)).unwrap();
+        let password_file = Py::new(py, password_file).unwrap();
+
+        let (login_request, client_state) =
+            client_start_login(py, PASSWORD.to_vec(), Some(suite), Some(SEED.to_vec()), None)
+                .unwrap();
+        let client_state = Py::new(py, client_state).unwrap();
+        let (login_response, server_state) = server_start_login(
+            py,
+            server_setup.borrow(py),
+            Some(password_file.borrow(py)),
+            login_request.bind(py).as_bytes().to_vec(),
+            CREDENTIAL_IDENTIFIER.to_vec(),
+            None,
+            Some(suite),
+            Some(SEED.to_vec()),
+            None,
+        )
+        .unwrap();
+        let server_state = Py::new(py, server_state).unwrap();
+
+        let (finalization, client_session_key, _export_key, _server_s_pk) = client_finish_login(
+            py,
+            client_state.borrow_mut(py),
+            PASSWORD.to_vec(),
+            login_response.bind(py).as_bytes().to_vec(),
+            None,
+            Some(suite),
+            true,
+            Some(SEED.to_vec()),
+            None,
+        )
+        .unwrap();
+        let server_session_key = server_finish_login(
+            py,
+            server_state.borrow_mut(py),
+            finalization.bind(py).as_bytes().to_vec(),
+            None,
+            Some(suite),
+            true,
+        )
+        .unwrap();
+
+        (
+            client_session_key.extract::<Vec<u8>>(py).unwrap(),
+            server_session_key.extract::<Vec<u8>>(py).unwrap(),
+        )
+    }
+
+    #[test]
+    fn seeded_round_trip_is_deterministic_and_agrees_for_every_suite() {
+        Python::with_gil(|py| {
+            for suite in SuiteId::available() {
+                let (client_key_a, server_key_a) = seeded_login_session_keys(py, suite);
+                assert_eq!(client_key_a, server_key_a, "suite {suite} session keys disagree");
+
+                let (client_key_b, server_key_b) = seeded_login_session_keys(py, suite);
+                assert_eq!(client_key_a, client_key_b, "suite {suite} not deterministic (client)");
+                assert_eq!(server_key_a, server_key_b, "suite {suite} not deterministic (server)");
+            }
+        });
+    }
+
+    /// A minimal stand-in for an external provider (HSM, FIPS module): always
+    /// returns a fixed byte repeated to the requested length.
+    #[pyclass(unsendable)]
+    struct FixedByteRng {
+        byte: u8,
+    }
+
+    #[pymethods]
+    impl FixedByteRng {
+        fn __call__(&self, nbytes: usize) -> Vec<u8> {
+            vec![self.byte; nbytes]
+        }
+    }
+
+    #[test]
+    fn rng_callable_is_used_in_place_of_seed_and_os_rng() {
+        Python::with_gil(|py| {
+            let rng_a = Py::new(py, FixedByteRng { byte: 0x42 }).unwrap();
+            let (message_a, _state_a) = client_start_login(
+                py,
+                PASSWORD.to_vec(),
+                None,
+                Some(SEED.to_vec()),
+                Some(rng_a.into_any()),
+            )
+            .unwrap();
+
+            let rng_b = Py::new(py, FixedByteRng { byte: 0x42 }).unwrap();
+            let (message_b, _state_b) = client_start_login(
+                py,
+                PASSWORD.to_vec(),
+                None,
+                Some([9u8; 32].to_vec()),
+                Some(rng_b.into_any()),
+            )
+            .unwrap();
+
+            // Same provider output in, same protocol message out, even
+            // though the two calls were given different seeds.
+            assert_eq!(
+                message_a.bind(py).as_bytes(),
+                message_b.bind(py).as_bytes()
+            );
+        });
+    }
+}
@@ -0,0 +1,102 @@
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+use crate::login::{server_finish_login, server_start_login};
+use crate::py_utils;
+use crate::sasl::{LoginDriver, SaslDriver};
+use crate::types::{
+    SecretBytes, ServerLoginParameters as PyServerLoginParameters, ServerLoginState,
+    ServerRegistration as PyServerRegistration, ServerSetup,
+};
+
+/// Drives `login.server_start_login`/`server_finish_login` directly, instead
+/// of going through an `OpaqueServer` instance — for callers who already
+/// manage `ServerSetup`/`ServerRegistration` through the stateless
+/// free-function API and don't want to also construct an `OpaqueServer`
+/// just to drive SASL.
+struct FreeFunctionBackend;
+
+impl LoginDriver for FreeFunctionBackend {
+    fn start_login(
+        &self,
+        py: Python<'_>,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+        request: Vec<u8>,
+        credential_identifier: Vec<u8>,
+        login_params: Py<PyServerLoginParameters>,
+    ) -> PyResult<(Vec<u8>, Py<ServerLoginState>)> {
+        let (response, state) = server_start_login(
+            py,
+            server_setup.borrow(py),
+            password_file.as_ref().map(|reg| reg.borrow(py)),
+            request,
+            credential_identifier,
+            Some(login_params.borrow(py)),
+            None,
+            None,
+            None,
+        )?;
+        let response = response.bind(py).as_bytes().to_vec();
+        Ok((response, Py::new(py, state)?))
+    }
+
+    fn finish_login(
+        &self,
+        py: Python<'_>,
+        state: Py<ServerLoginState>,
+        finalization: Vec<u8>,
+    ) -> PyResult<Py<PyAny>> {
+        server_finish_login(py, state.borrow_mut(py), finalization, None, None, false)
+    }
+}
+
+/// The same Dovecot-style SASL continuation as `opaque_ke.sasl.SaslServer`,
+/// but driven through `FreeFunctionBackend`.
+#[pyclass(unsendable, name = "SaslServer")]
+pub struct LoginSaslServer {
+    driver: SaslDriver<FreeFunctionBackend>,
+}
+
+#[pymethods]
+impl LoginSaslServer {
+    #[new]
+    fn new() -> Self {
+        Self {
+            driver: SaslDriver::new(FreeFunctionBackend),
+        }
+    }
+
+    /// Handles the initial `AUTH <id> OPAQUE <base64> user=<name> ...` line
+    /// and returns the `CONT <id> <base64>` reply to send back.
+    #[pyo3(signature = (line, server_setup, password_file=None))]
+    fn start(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+    ) -> PyResult<String> {
+        self.driver.start(py, line, server_setup, password_file)
+    }
+
+    /// Handles the client's `CONT <id> <base64>` finalization line and
+    /// returns the terminal `OK <id> user=<name>` or `FAIL <id>` line,
+    /// together with the session key on success so the caller can bind it
+    /// to whatever transport carried this exchange.
+    #[pyo3(name = "continue")]
+    fn r#continue(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+    ) -> PyResult<(String, Option<SecretBytes>)> {
+        self.driver.r#continue(py, line)
+    }
+}
+
+pub(crate) fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let module = py_utils::new_submodule(py, parent, "sasl")?;
+    module.add_class::<LoginSaslServer>()?;
+    py_utils::add_submodule(py, parent, "sasl", &module)?;
+    Ok(())
+}
@@ -1,9 +1,36 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
 
-use crate::errors::invalid_state_err;
+use crate::errors::{invalid_state_err, serialization_err};
 use crate::suite::SuiteId;
 
+const TAGGED_MAGIC: [u8; 2] = *b"OK";
+const TAGGED_VERSION: u8 = 1;
+
+/// Prepends a short self-describing header (magic, version, `SuiteId` tag)
+/// to an opaque-ke `serialize()` payload so `deserialize_tagged` can recover
+/// the cipher suite without an out-of-band `suite` argument.
+pub(crate) fn encode_tagged(suite: SuiteId, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + payload.len());
+    out.extend_from_slice(&TAGGED_MAGIC);
+    out.push(TAGGED_VERSION);
+    out.push(suite.tag());
+    out.extend_from_slice(payload);
+    out
+}
+
+pub(crate) fn decode_tagged(data: &[u8]) -> PyResult<(SuiteId, &[u8])> {
+    if data.len() < 4 || data[0..2] != TAGGED_MAGIC {
+        return Err(serialization_err("not a recognized tagged OPAQUE envelope"));
+    }
+    if data[2] != TAGGED_VERSION {
+        return Err(serialization_err("unsupported tagged envelope version"));
+    }
+    let suite = SuiteId::from_tag(data[3])
+        .ok_or_else(|| serialization_err("tagged envelope has an unknown cipher suite id"))?;
+    Ok((suite, &data[4..]))
+}
+
 fn parent_name(parent: &Bound<'_, PyModule>) -> PyResult<String> {
     parent.getattr("__name__")?.extract::<String>()
 }
@@ -18,6 +45,18 @@ pub fn ensure_suite(expected: SuiteId, actual: SuiteId, label: &str) -> PyResult
     }
 }
 
+/// Expands to the `match suite { SuiteId::X => ... }` that every
+/// `*_start_*` free function needs: call `$start::<Suite>::start(rng,
+/// password)` for the matched suite, wrap any failure with `to_py_err`, then
+/// box up the serialized message and per-suite state. `after_start`, if
+/// given, runs once the call succeeds and before the state is boxed up — for
+/// example `client_start_login`'s `rng.take_error()?` to surface a deferred
+/// error from a callable `rng`.
+///
+/// Only fits functions whose per-suite work is exactly "call `start`, box
+/// the result" — `server_start_login`'s suite match also threads
+/// `credential_identifier`/`parameters`/`record`, which this macro doesn't
+/// model, so that one stays a hand-written match.
 macro_rules! per_suite_dispatch {
     (
         suite = $suite:expr,
@@ -27,6 +66,7 @@ macro_rules! per_suite_dispatch {
         start = $start:ident,
         state_type = $state_ty:ident,
         state_inner = $state_inner_ty:ident,
+        $(after_start = $after:expr,)?
         [ $( ($suite_id:path, $suite_ty:ty, $state_variant:ident) ),+ $(,)? ]
     ) => {
         match $suite {
@@ -34,6 +74,7 @@ macro_rules! per_suite_dispatch {
                 $suite_id => {
                     let result = $start::<$suite_ty>::start(&mut $rng, &$password)
                         .map_err(crate::errors::to_py_err)?;
+                    $( $after; )?
                     let message = result.message.serialize().to_vec();
                     Ok((
                         crate::py_utils::to_pybytes($py, &message),
@@ -102,3 +143,17 @@ pub fn add_submodule<'py>(
 pub fn to_pybytes(py: Python<'_>, data: &[u8]) -> Py<PyBytes> {
     PyBytes::new(py, data).into()
 }
+
+pub fn to_secret_bytes(data: Vec<u8>) -> crate::types::SecretBytes {
+    crate::types::SecretBytes::new(data)
+}
+
+/// Wraps `data` as a redacting `SecretBytes`, unless `legacy_bytes` opts the
+/// caller back into plain, trivially-loggable `bytes`.
+pub(crate) fn secret_or_bytes(py: Python<'_>, data: Vec<u8>, legacy_bytes: bool) -> PyResult<Py<PyAny>> {
+    if legacy_bytes {
+        Ok(to_pybytes(py, &data).into())
+    } else {
+        Ok(Py::new(py, to_secret_bytes(data))?.into())
+    }
+}
@@ -5,7 +5,9 @@ use opaque_ke::{
 };
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
+use zeroize::Zeroizing;
 
+use crate::encoding;
 use crate::errors::{invalid_state_err, to_py_err};
 use crate::py_utils;
 use crate::suite::{
@@ -15,7 +17,7 @@ use crate::suite::{
 use crate::types::{
     ClientRegistrationFinishParameters as PyClientRegistrationFinishParameters,
     ClientRegistrationState, ClientRegistrationStateInner,
-    ServerRegistration as PyServerRegistration, ServerRegistrationInner, ServerSetup,
+    ServerRegistration as PyServerRegistration, ServerRegistrationInner, SecretBytes, ServerSetup,
     ServerSetupInner,
 };
 
@@ -31,85 +33,47 @@ fn ensure_suite(expected: SuiteId, actual: SuiteId, label: &str) -> PyResult<()>
 
 #[pyfunction(name = "start_registration")]
 #[pyo3(signature = (password, suite=None))]
-fn client_start_registration(
+pub(crate) fn client_start_registration(
     py: Python<'_>,
     password: Vec<u8>,
     suite: Option<&str>,
 ) -> PyResult<(Py<PyBytes>, ClientRegistrationState)> {
+    let password = Zeroizing::new(password);
     let suite = parse_suite(suite)?;
     let mut rng = OsRng;
-    match suite {
-        SuiteId::Ristretto255Sha512 => {
-            let result = ClientRegistration::<Ristretto255Sha512>::start(&mut rng, &password)
-                .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientRegistrationState {
-                    inner: ClientRegistrationStateInner::Ristretto255Sha512(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P256Sha256 => {
-            let result =
-                ClientRegistration::<P256Sha256>::start(&mut rng, &password).map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientRegistrationState {
-                    inner: ClientRegistrationStateInner::P256Sha256(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P384Sha384 => {
-            let result =
-                ClientRegistration::<P384Sha384>::start(&mut rng, &password).map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientRegistrationState {
-                    inner: ClientRegistrationStateInner::P384Sha384(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::P521Sha512 => {
-            let result =
-                ClientRegistration::<P521Sha512>::start(&mut rng, &password).map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientRegistrationState {
-                    inner: ClientRegistrationStateInner::P521Sha512(Some(result.state)),
-                },
-            ))
-        }
-        SuiteId::MlKem768Ristretto255Sha512 => {
-            let result =
-                ClientRegistration::<MlKem768Ristretto255Sha512>::start(&mut rng, &password)
-                    .map_err(to_py_err)?;
-            let message = result.message.serialize().to_vec();
-            Ok((
-                py_utils::to_pybytes(py, &message),
-                ClientRegistrationState {
-                    inner: ClientRegistrationStateInner::MlKem768Ristretto255Sha512(Some(
-                        result.state,
-                    )),
-                },
-            ))
-        }
-    }
+    py_utils::per_suite_dispatch!(
+        suite = suite,
+        py = py,
+        rng = rng,
+        password = password,
+        start = ClientRegistration,
+        state_type = ClientRegistrationState,
+        state_inner = ClientRegistrationStateInner,
+        [
+            (SuiteId::Ristretto255Sha512, Ristretto255Sha512, Ristretto255Sha512),
+            (SuiteId::P256Sha256, P256Sha256, P256Sha256),
+            (SuiteId::P384Sha384, P384Sha384, P384Sha384),
+            (SuiteId::P521Sha512, P521Sha512, P521Sha512),
+            (
+                SuiteId::MlKem768Ristretto255Sha512,
+                MlKem768Ristretto255Sha512,
+                MlKem768Ristretto255Sha512
+            ),
+        ]
+    )
 }
 
 #[pyfunction(name = "finish_registration")]
 #[pyo3(signature = (state, password, response, params=None, suite=None))]
-fn client_finish_registration(
+pub(crate) fn client_finish_registration(
     py: Python<'_>,
     mut state: PyRefMut<'_, ClientRegistrationState>,
     password: Vec<u8>,
     response: Vec<u8>,
     params: Option<PyRef<'_, PyClientRegistrationFinishParameters>>,
     suite: Option<&str>,
-) -> PyResult<(Py<PyBytes>, Py<PyBytes>)> {
+) -> PyResult<(Py<PyBytes>, SecretBytes)> {
+    let password = Zeroizing::new(password);
     let state_suite = state.suite_id();
     if let Some(requested) = suite {
         let requested = parse_suite(Some(requested))?;
@@ -145,7 +109,7 @@ fn client_finish_registration(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::to_secret_bytes(export_key),
             ))
         }
         SuiteId::P256Sha256 => {
@@ -159,7 +123,7 @@ fn client_finish_registration(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::to_secret_bytes(export_key),
             ))
         }
         SuiteId::P384Sha384 => {
@@ -173,7 +137,7 @@ fn client_finish_registration(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::to_secret_bytes(export_key),
             ))
         }
         SuiteId::P521Sha512 => {
@@ -187,7 +151,7 @@ fn client_finish_registration(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::to_secret_bytes(export_key),
             ))
         }
         SuiteId::MlKem768Ristretto255Sha512 => {
@@ -202,7 +166,7 @@ fn client_finish_registration(
             let export_key = result.export_key.to_vec();
             Ok((
                 py_utils::to_pybytes(py, &message),
-                py_utils::to_pybytes(py, &export_key),
+                py_utils::to_secret_bytes(export_key),
             ))
         }
     }
@@ -210,7 +174,7 @@ fn client_finish_registration(
 
 #[pyfunction(name = "start_registration")]
 #[pyo3(signature = (server_setup, request, credential_identifier, suite=None))]
-fn server_start_registration(
+pub(crate) fn server_start_registration(
     py: Python<'_>,
     server_setup: PyRef<'_, ServerSetup>,
     request: Vec<u8>,
@@ -277,13 +241,32 @@ fn server_start_registration(
     }
 }
 
+/// `suite=None` auto-detects the cipher suite from an `encoding.wrap()`
+/// envelope embedded in `upload`, falling back to the default suite only if
+/// `upload` doesn't look tagged at all (see `encoding::try_unwrap`).
+///
+/// That detection is a heuristic, not a verified format check: a raw,
+/// never-wrapped `upload` can in principle collide with the tag pattern and
+/// be silently (and wrongly) unwrapped. If `upload` isn't guaranteed to have
+/// come from `encoding.wrap()` — e.g. it was deserialized straight from an
+/// `RegistrationUpload::serialize()` call with the suite tracked
+/// out-of-band — always pass `suite` explicitly instead of relying on this
+/// fallback.
 #[pyfunction(name = "finish_registration")]
 #[pyo3(signature = (upload, suite=None))]
-fn server_finish_registration(
+pub(crate) fn server_finish_registration(
     upload: Vec<u8>,
     suite: Option<&str>,
 ) -> PyResult<PyServerRegistration> {
-    let suite = parse_suite(suite)?;
+    // With no explicit suite, prefer one embedded via encoding.wrap() over
+    // silently defaulting to Ristretto255Sha512.
+    let (suite, upload) = match suite {
+        Some(suite) => (parse_suite(Some(suite))?, upload),
+        None => match encoding::try_unwrap(&upload) {
+            Some((suite, payload)) => (suite, payload.to_vec()),
+            None => (parse_suite(None)?, upload),
+        },
+    };
     match suite {
         SuiteId::Ristretto255Sha512 => {
             let upload = RegistrationUpload::<Ristretto255Sha512>::deserialize(&upload)
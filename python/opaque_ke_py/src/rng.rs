@@ -0,0 +1,152 @@
+use opaque_ke::rand::rngs::OsRng;
+use opaque_ke::rand::{CryptoRng, RngCore, SeedableRng};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand_chacha::ChaCha20Rng;
+
+/// The system CSPRNG, a `ChaCha20Rng` seeded from caller-supplied bytes, or a
+/// caller-supplied Python callable standing in for an external entropy
+/// source (an HSM, a FIPS module, a fixed byte stream for a test vector).
+/// The non-`Os` variants exist so tests and fixture generators can reproduce
+/// a transcript, or so a deployment can swap in its own provider; neither is
+/// ever reached implicitly.
+pub(crate) enum AnyRng {
+    Os(OsRng),
+    Seeded(ChaCha20Rng),
+    Callable(CallableRng),
+}
+
+/// Adapts a Python callable of the shape `(nbytes: int) -> bytes` into
+/// `RngCore`. The callable is invoked once per `fill_bytes`/`next_u32`/
+/// `next_u64` call with the exact number of bytes needed; it must return
+/// that many bytes. `RngCore::fill_bytes`/`next_u32`/`next_u64` are
+/// infallible by signature, so a raise or a wrong-length return is stashed
+/// in `error` instead of unwinding through opaque-ke's internals — the
+/// caller must check `AnyRng::take_error` once the surrounding opaque-ke
+/// call returns, before trusting its result.
+pub(crate) struct CallableRng {
+    callable: Py<PyAny>,
+    error: Option<PyErr>,
+}
+
+impl CallableRng {
+    /// Always invokes the callable, even after a previous call has already
+    /// failed — some suites reject-sample by calling `fill_bytes` in a loop
+    /// until the drawn bytes satisfy a validity check, and short-circuiting
+    /// to a fixed (e.g. all-zero) buffer after the first error would make
+    /// such a loop spin forever instead of ever reaching `take_error`. Only
+    /// the first error is kept; later ones are assumed to be the same
+    /// underlying problem.
+    fn fill(&mut self, dest: &mut [u8]) {
+        let result = Python::with_gil(|py| -> PyResult<()> {
+            let bytes: Vec<u8> = self.callable.call1(py, (dest.len(),))?.extract(py)?;
+            if bytes.len() != dest.len() {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "rng callable returned {} bytes, expected {}",
+                    bytes.len(),
+                    dest.len()
+                )));
+            }
+            dest.copy_from_slice(&bytes);
+            Ok(())
+        });
+        if let Err(err) = result {
+            if self.error.is_none() {
+                self.error = Some(err);
+            }
+        }
+    }
+}
+
+impl RngCore for AnyRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            AnyRng::Os(rng) => rng.next_u32(),
+            AnyRng::Seeded(rng) => rng.next_u32(),
+            AnyRng::Callable(rng) => {
+                let mut buf = [0u8; 4];
+                rng.fill(&mut buf);
+                u32::from_le_bytes(buf)
+            }
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            AnyRng::Os(rng) => rng.next_u64(),
+            AnyRng::Seeded(rng) => rng.next_u64(),
+            AnyRng::Callable(rng) => {
+                let mut buf = [0u8; 8];
+                rng.fill(&mut buf);
+                u64::from_le_bytes(buf)
+            }
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            AnyRng::Os(rng) => rng.fill_bytes(dest),
+            AnyRng::Seeded(rng) => rng.fill_bytes(dest),
+            AnyRng::Callable(rng) => rng.fill(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), opaque_ke::rand::Error> {
+        match self {
+            AnyRng::Os(rng) => rng.try_fill_bytes(dest),
+            AnyRng::Seeded(rng) => rng.try_fill_bytes(dest),
+            AnyRng::Callable(rng) => {
+                rng.fill(dest);
+                match rng.error.take() {
+                    Some(err) => Err(opaque_ke::rand::Error::new(err)),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl CryptoRng for AnyRng {}
+
+impl AnyRng {
+    /// Surfaces a deferred failure from a `rng` callable (a raise, or a
+    /// wrong-length return) as a normal `PyErr`. Must be called once the
+    /// opaque-ke operation driven by this RNG returns, since `RngCore`'s own
+    /// methods can't propagate it directly. A no-op for `Os`/`Seeded`.
+    pub(crate) fn take_error(&mut self) -> PyResult<()> {
+        if let AnyRng::Callable(rng) = self {
+            if let Some(err) = rng.error.take() {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds the RNG a constructor should use, in order of precedence: `rng` (a
+/// Python callable of shape `(nbytes: int) -> bytes`, for an external
+/// provider such as an HSM or FIPS module, or for a fixed byte stream in a
+/// test), then `seed` (a deterministic `ChaCha20Rng` for known-answer
+/// vectors), then `OsRng`. Both `rng` and `seed` are opt-in only and must
+/// never be reached implicitly.
+///
+/// Only `client_start_login`/`client_finish_login`/`server_start_login`
+/// expose `rng`; `ServerSetup`'s constructors and `OpaqueServer` take only
+/// `seed`, and the registration flows take neither and always use `OsRng`.
+pub(crate) fn make_rng(seed: Option<&[u8]>, rng: Option<Py<PyAny>>) -> PyResult<AnyRng> {
+    if let Some(callable) = rng {
+        return Ok(AnyRng::Callable(CallableRng {
+            callable,
+            error: None,
+        }));
+    }
+    match seed {
+        Some(seed) => {
+            let seed: [u8; 32] = seed.try_into().map_err(|_| {
+                PyErr::new::<PyValueError, _>("seed must be exactly 32 bytes")
+            })?;
+            Ok(AnyRng::Seeded(ChaCha20Rng::from_seed(seed)))
+        }
+        None => Ok(AnyRng::Os(OsRng)),
+    }
+}
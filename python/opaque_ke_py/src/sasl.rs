@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+use crate::errors::serialization_err;
+use crate::py_utils;
+use crate::server::OpaqueServer;
+use crate::types::{
+    SecretBytes, ServerLoginParameters as PyServerLoginParameters, ServerLoginState,
+    ServerRegistration as PyServerRegistration, ServerSetup,
+};
+
+/// A login in progress, keyed by the SASL request id until `continue` is
+/// called with the matching `CONT` line.
+struct PendingLogin {
+    state: Py<ServerLoginState>,
+    credential_identifier: Vec<u8>,
+}
+
+/// The half of `start_login`/`finish_login` that differs between the two
+/// SASL front ends: `SaslServer` drives an `OpaqueServer` instance through
+/// its Python methods, `opaque_ke.login.sasl.SaslServer` calls the
+/// free-function API directly. Everything else about the two-step
+/// continuation — parsing lines, tracking `PendingLogin`, formatting the
+/// `CONT`/`OK`/`FAIL` replies — is identical and lives once in `SaslDriver`.
+pub(crate) trait LoginDriver {
+    fn start_login(
+        &self,
+        py: Python<'_>,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+        request: Vec<u8>,
+        credential_identifier: Vec<u8>,
+        login_params: Py<PyServerLoginParameters>,
+    ) -> PyResult<(Vec<u8>, Py<ServerLoginState>)>;
+
+    fn finish_login(
+        &self,
+        py: Python<'_>,
+        state: Py<ServerLoginState>,
+        finalization: Vec<u8>,
+    ) -> PyResult<Py<PyAny>>;
+}
+
+/// Drives a `LoginDriver` as a two-step SASL continuation exchange modeled
+/// on the Dovecot authentication protocol:
+///
+/// ```text
+/// C: AUTH <id> OPAQUE <base64 credential-request> user=<name> service=<svc>
+/// S: CONT <id> <base64 credential-response>
+/// C: CONT <id> <base64 credential-finalization>
+/// S: OK <id> user=<name>   (or FAIL <id>)
+/// ```
+///
+/// This lets a mail server speaking the Dovecot line protocol drop OPAQUE in
+/// as a SASL mechanism without hand-rolling the exchange, regardless of
+/// which backend drives the actual login.
+pub(crate) struct SaslDriver<D> {
+    backend: D,
+    pending: HashMap<u64, PendingLogin>,
+}
+
+impl<D: LoginDriver> SaslDriver<D> {
+    pub(crate) fn new(backend: D) -> Self {
+        Self {
+            backend,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Handles the initial `AUTH <id> OPAQUE <base64> user=<name> ...` line
+    /// and returns the `CONT <id> <base64>` reply to send back.
+    pub(crate) fn start(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+    ) -> PyResult<String> {
+        let (id, request, params) = parse_auth_line(line)?;
+        let credential_identifier = params
+            .get("user")
+            .ok_or_else(|| serialization_err("AUTH line is missing a 'user=' parameter"))?
+            .as_bytes()
+            .to_vec();
+        let context = build_context(&params);
+        let login_params = Py::new(py, PyServerLoginParameters::with_context(context))?;
+
+        let (response, state) = self.backend.start_login(
+            py,
+            server_setup,
+            password_file,
+            request,
+            credential_identifier.clone(),
+            login_params,
+        )?;
+
+        self.pending.insert(
+            id,
+            PendingLogin {
+                state,
+                credential_identifier,
+            },
+        );
+        Ok(format!("CONT {id} {}", STANDARD.encode(response)))
+    }
+
+    /// Handles the client's `CONT <id> <base64>` finalization line and
+    /// returns the terminal `OK <id> user=<name>` or `FAIL <id>` line,
+    /// together with the session key on success so the caller can bind it
+    /// to whatever transport carried this exchange.
+    pub(crate) fn r#continue(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+    ) -> PyResult<(String, Option<SecretBytes>)> {
+        let (id, finalization) = parse_cont_line(line)?;
+        let pending = self
+            .pending
+            .remove(&id)
+            .ok_or_else(|| serialization_err("no pending SASL login for this request id"))?;
+
+        let outcome = self.backend.finish_login(py, pending.state, finalization);
+        match outcome {
+            Ok(session_key) => Ok((
+                format!(
+                    "OK {id} user={}",
+                    String::from_utf8_lossy(&pending.credential_identifier)
+                ),
+                Some(session_key.extract::<SecretBytes>(py)?),
+            )),
+            Err(_) => Ok((format!("FAIL {id}"), None)),
+        }
+    }
+}
+
+/// Drives an `OpaqueServer` instance through its Python `start_login`/
+/// `finish_login` methods.
+struct OpaqueServerBackend {
+    server: Py<OpaqueServer>,
+}
+
+impl LoginDriver for OpaqueServerBackend {
+    fn start_login(
+        &self,
+        py: Python<'_>,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+        request: Vec<u8>,
+        credential_identifier: Vec<u8>,
+        login_params: Py<PyServerLoginParameters>,
+    ) -> PyResult<(Vec<u8>, Py<ServerLoginState>)> {
+        let server = self.server.bind(py);
+        server
+            .call_method1(
+                "start_login",
+                (
+                    server_setup,
+                    password_file,
+                    request,
+                    credential_identifier,
+                    login_params,
+                ),
+            )?
+            .extract()
+    }
+
+    fn finish_login(
+        &self,
+        py: Python<'_>,
+        state: Py<ServerLoginState>,
+        finalization: Vec<u8>,
+    ) -> PyResult<Py<PyAny>> {
+        let server = self.server.bind(py);
+        Ok(server
+            .call_method1("finish_login", (state, finalization, py.None()))?
+            .unbind())
+    }
+}
+
+/// The same Dovecot-style SASL continuation as `opaque_ke.login.sasl.SaslServer`,
+/// but driven through an `OpaqueServer` instance.
+#[pyclass(unsendable)]
+pub struct SaslServer {
+    driver: SaslDriver<OpaqueServerBackend>,
+}
+
+fn parse_kv_params<'a>(tokens: impl Iterator<Item = &'a str>) -> HashMap<String, String> {
+    tokens
+        .filter_map(|token| token.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Parses `AUTH <id> OPAQUE <base64> key=value ...`.
+pub(crate) fn parse_auth_line(line: &str) -> PyResult<(u64, Vec<u8>, HashMap<String, String>)> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("AUTH") {
+        return Err(serialization_err("expected a SASL 'AUTH' line"));
+    }
+    let id: u64 = tokens
+        .next()
+        .ok_or_else(|| serialization_err("AUTH line is missing a request id"))?
+        .parse()
+        .map_err(|_| serialization_err("AUTH line has a non-numeric request id"))?;
+    if tokens.next() != Some("OPAQUE") {
+        return Err(serialization_err("AUTH line does not name the OPAQUE mechanism"));
+    }
+    let payload = tokens
+        .next()
+        .ok_or_else(|| serialization_err("AUTH line is missing the initial response"))?;
+    let request = STANDARD
+        .decode(payload)
+        .map_err(|err| serialization_err(&err.to_string()))?;
+    Ok((id, request, parse_kv_params(tokens)))
+}
+
+/// Parses `CONT <id> <base64>`.
+pub(crate) fn parse_cont_line(line: &str) -> PyResult<(u64, Vec<u8>)> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("CONT") {
+        return Err(serialization_err("expected a SASL 'CONT' line"));
+    }
+    let id: u64 = tokens
+        .next()
+        .ok_or_else(|| serialization_err("CONT line is missing a request id"))?
+        .parse()
+        .map_err(|_| serialization_err("CONT line has a non-numeric request id"))?;
+    let payload = tokens
+        .next()
+        .ok_or_else(|| serialization_err("CONT line is missing its payload"))?;
+    let decoded = STANDARD
+        .decode(payload)
+        .map_err(|err| serialization_err(&err.to_string()))?;
+    Ok((id, decoded))
+}
+
+/// Builds the OPAQUE `context` bytes from the AUTH line's key/value params
+/// (everything but `user`, which identifies the account rather than
+/// describing the session).
+pub(crate) fn build_context(params: &HashMap<String, String>) -> Vec<u8> {
+    let mut pairs: Vec<(&String, &String)> =
+        params.iter().filter(|(key, _)| key.as_str() != "user").collect();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(";")
+        .into_bytes()
+}
+
+#[pymethods]
+impl SaslServer {
+    #[new]
+    fn new(server: Py<OpaqueServer>) -> Self {
+        Self {
+            driver: SaslDriver::new(OpaqueServerBackend { server }),
+        }
+    }
+
+    /// Handles the initial `AUTH <id> OPAQUE <base64> user=<name> ...` line
+    /// and returns the `CONT <id> <base64>` reply to send back.
+    #[pyo3(signature = (line, server_setup, password_file=None))]
+    fn start(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+        server_setup: Py<ServerSetup>,
+        password_file: Option<Py<PyServerRegistration>>,
+    ) -> PyResult<String> {
+        self.driver.start(py, line, server_setup, password_file)
+    }
+
+    /// Handles the client's `CONT <id> <base64>` finalization line and
+    /// returns the terminal `OK <id> user=<name>` or `FAIL <id>` line,
+    /// together with the session key on success so the caller can bind it
+    /// to whatever transport carried this exchange.
+    #[pyo3(name = "continue")]
+    fn r#continue(
+        &mut self,
+        py: Python<'_>,
+        line: &str,
+    ) -> PyResult<(String, Option<SecretBytes>)> {
+        self.driver.r#continue(py, line)
+    }
+}
+
+pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let module = py_utils::new_submodule(py, parent, "sasl")?;
+    module.add_class::<SaslServer>()?;
+    py_utils::add_submodule(py, parent, "sasl", &module)?;
+    Ok(())
+}
@@ -1,4 +1,3 @@
-use opaque_ke::rand::rngs::OsRng;
 use opaque_ke::{
     CredentialFinalization, CredentialRequest, RegistrationRequest, RegistrationUpload,
     ServerLogin, ServerLoginParameters, ServerRegistration,
@@ -6,8 +5,9 @@ use opaque_ke::{
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
 
-use crate::errors::{invalid_state_err, to_py_err};
+use crate::errors::{invalid_state_err, throttled_err, to_py_err};
 use crate::py_utils;
+use crate::rng::make_rng;
 use crate::suite::{parse_suite, Ristretto255Sha512, SuiteId};
 use crate::suite::MlKem768Ristretto255Sha512;
 use crate::suite::P256Sha256;
@@ -22,14 +22,23 @@ use crate::types::{
 #[pyclass(unsendable)]
 pub struct OpaqueServer {
     suite: SuiteId,
+    seed: Option<Vec<u8>>,
 }
 
 #[pymethods]
 impl OpaqueServer {
+    /// `seed`, if given, must be exactly 32 bytes and switches every
+    /// randomized step this server performs (currently `start_login`'s
+    /// nonce/blind generation) from `OsRng` to a `ChaCha20Rng` seeded from it,
+    /// so a full protocol transcript can be reproduced byte-for-byte. This is
+    /// strictly for known-answer test vectors — never set it outside a test
+    /// harness.
     #[new]
-    fn new(suite: Option<&str>) -> PyResult<Self> {
+    #[pyo3(signature = (suite=None, seed=None))]
+    fn new(suite: Option<&str>, seed: Option<Vec<u8>>) -> PyResult<Self> {
         Ok(Self {
             suite: parse_suite(suite)?,
+            seed,
         })
     }
 
@@ -169,11 +178,71 @@ impl OpaqueServer {
         }
     }
 
+    /// Serializes a `ServerRegistration` into a self-describing envelope
+    /// (magic, format version, suite tag, then the opaque-ke bytes) so it can
+    /// be persisted in a database column or LDAP attribute and later
+    /// recognized as belonging to this server's cipher suite.
+    fn export_password_file(
+        &self,
+        py: Python<'_>,
+        password_file: PyRef<'_, PyServerRegistration>,
+    ) -> PyResult<Py<PyBytes>> {
+        if password_file.suite_id() != self.suite {
+            return Err(invalid_state_err(
+                "ServerRegistration does not match this server instance",
+            ));
+        }
+        password_file.serialize_tagged(py)
+    }
+
+    /// Inverse of `export_password_file`. Validates the envelope's suite tag
+    /// against this server's own cipher suite before deserializing, so a
+    /// password file exported under one suite cannot be silently loaded into
+    /// a server configured for another.
+    fn import_password_file(&self, data: Vec<u8>) -> PyResult<PyServerRegistration> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        py_utils::ensure_suite(self.suite, suite, "password file")?;
+        PyServerRegistration::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    /// Serializes a `ServerSetup` into the same self-describing envelope as
+    /// `export_password_file`, for persisting the server's long-term keys.
+    fn export_server_setup(
+        &self,
+        py: Python<'_>,
+        server_setup: PyRef<'_, ServerSetup>,
+    ) -> PyResult<Py<PyBytes>> {
+        if server_setup.suite_id() != self.suite {
+            return Err(invalid_state_err(
+                "ServerSetup does not match this server instance",
+            ));
+        }
+        Ok(server_setup.serialize_tagged(py))
+    }
+
+    /// Inverse of `export_server_setup`, rejecting a blob whose embedded
+    /// suite tag doesn't match this server's cipher suite instead of
+    /// deserializing it under the wrong group.
+    fn import_server_setup(&self, data: Vec<u8>) -> PyResult<ServerSetup> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        py_utils::ensure_suite(self.suite, suite, "server setup")?;
+        ServerSetup::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    /// Starts a login flow. `password_file=None` is the account-enumeration-
+    /// resistant path: opaque-ke synthesizes a dummy evaluated credential
+    /// from `server_setup`'s OPRF seed and `credential_identifier`, producing
+    /// a `CredentialResponse` that is byte-indistinguishable from a real
+    /// account's. Callers should invoke this the same way regardless of
+    /// whether the account exists, and must still drive the resulting state
+    /// through `finish_login` so request timing doesn't leak existence
+    /// either.
+    #[pyo3(signature = (server_setup, password_file, request, credential_identifier, params=None))]
     fn start_login(
         &self,
         py: Python<'_>,
         server_setup: PyRef<'_, ServerSetup>,
-        password_file: PyRef<'_, PyServerRegistration>,
+        password_file: Option<PyRef<'_, PyServerRegistration>>,
         request: Vec<u8>,
         credential_identifier: Vec<u8>,
         params: Option<PyRef<'_, PyServerLoginParameters>>,
@@ -183,12 +252,22 @@ impl OpaqueServer {
                 "ServerSetup does not match this server instance",
             ));
         }
-        if password_file.suite_id() != self.suite {
-            return Err(invalid_state_err(
-                "ServerRegistration does not match this server instance",
-            ));
+        if let Some(password_file) = &password_file {
+            if password_file.suite_id() != self.suite {
+                return Err(invalid_state_err(
+                    "ServerRegistration does not match this server instance",
+                ));
+            }
         }
-        let mut rng = OsRng;
+        if let Some(throttle) = params.as_ref().and_then(|params| params.throttle()) {
+            if !throttle
+                .borrow_mut(py)
+                .register_attempt(py, credential_identifier.clone())?
+            {
+                return Err(throttled_err("too many login attempts for this identifier"));
+            }
+        }
+        let mut rng = make_rng(self.seed.as_deref(), None)?;
         let identifiers = params
             .as_ref()
             .and_then(|params| params.identifiers().cloned());
@@ -207,15 +286,26 @@ impl OpaqueServer {
         } else {
             ServerLoginParameters::default()
         };
-        match (&server_setup.inner, &password_file.inner) {
-            (ServerSetupInner::Ristretto255Sha512(setup), ServerRegistrationInner::Ristretto255Sha512(reg)) => {
+        match &server_setup.inner {
+            ServerSetupInner::Ristretto255Sha512(setup) => {
+                let record = match &password_file {
+                    Some(password_file) => match &password_file.inner {
+                        ServerRegistrationInner::Ristretto255Sha512(reg) => Some(reg.clone()),
+                        _ => {
+                            return Err(invalid_state_err(
+                                "ServerSetup and ServerRegistration use different cipher suites",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
                 let request =
                     CredentialRequest::<Ristretto255Sha512>::deserialize(&request)
                         .map_err(to_py_err)?;
                 let result = ServerLogin::<Ristretto255Sha512>::start(
                     &mut rng,
                     setup,
-                    Some(reg.clone()),
+                    record,
                     request,
                     &credential_identifier,
                     parameters,
@@ -226,17 +316,29 @@ impl OpaqueServer {
                     py_utils::to_pybytes(py, &message),
                     ServerLoginState {
                         inner: ServerLoginStateInner::Ristretto255Sha512(Some(result.state)),
+                        credential_identifier: Some(credential_identifier.clone()),
                     },
                 ))
             }
-            (ServerSetupInner::P256Sha256(setup), ServerRegistrationInner::P256Sha256(reg)) => {
+            ServerSetupInner::P256Sha256(setup) => {
+                let record = match &password_file {
+                    Some(password_file) => match &password_file.inner {
+                        ServerRegistrationInner::P256Sha256(reg) => Some(reg.clone()),
+                        _ => {
+                            return Err(invalid_state_err(
+                                "ServerSetup and ServerRegistration use different cipher suites",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
                 let request =
                     CredentialRequest::<P256Sha256>::deserialize(&request)
                         .map_err(to_py_err)?;
                 let result = ServerLogin::<P256Sha256>::start(
                     &mut rng,
                     setup,
-                    Some(reg.clone()),
+                    record,
                     request,
                     &credential_identifier,
                     parameters,
@@ -247,17 +349,29 @@ impl OpaqueServer {
                     py_utils::to_pybytes(py, &message),
                     ServerLoginState {
                         inner: ServerLoginStateInner::P256Sha256(Some(result.state)),
+                        credential_identifier: Some(credential_identifier.clone()),
                     },
                 ))
             }
-            (ServerSetupInner::P384Sha384(setup), ServerRegistrationInner::P384Sha384(reg)) => {
+            ServerSetupInner::P384Sha384(setup) => {
+                let record = match &password_file {
+                    Some(password_file) => match &password_file.inner {
+                        ServerRegistrationInner::P384Sha384(reg) => Some(reg.clone()),
+                        _ => {
+                            return Err(invalid_state_err(
+                                "ServerSetup and ServerRegistration use different cipher suites",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
                 let request =
                     CredentialRequest::<P384Sha384>::deserialize(&request)
                         .map_err(to_py_err)?;
                 let result = ServerLogin::<P384Sha384>::start(
                     &mut rng,
                     setup,
-                    Some(reg.clone()),
+                    record,
                     request,
                     &credential_identifier,
                     parameters,
@@ -268,17 +382,29 @@ impl OpaqueServer {
                     py_utils::to_pybytes(py, &message),
                     ServerLoginState {
                         inner: ServerLoginStateInner::P384Sha384(Some(result.state)),
+                        credential_identifier: Some(credential_identifier.clone()),
                     },
                 ))
             }
-            (ServerSetupInner::P521Sha512(setup), ServerRegistrationInner::P521Sha512(reg)) => {
+            ServerSetupInner::P521Sha512(setup) => {
+                let record = match &password_file {
+                    Some(password_file) => match &password_file.inner {
+                        ServerRegistrationInner::P521Sha512(reg) => Some(reg.clone()),
+                        _ => {
+                            return Err(invalid_state_err(
+                                "ServerSetup and ServerRegistration use different cipher suites",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
                 let request =
                     CredentialRequest::<P521Sha512>::deserialize(&request)
                         .map_err(to_py_err)?;
                 let result = ServerLogin::<P521Sha512>::start(
                     &mut rng,
                     setup,
-                    Some(reg.clone()),
+                    record,
                     request,
                     &credential_identifier,
                     parameters,
@@ -289,17 +415,31 @@ impl OpaqueServer {
                     py_utils::to_pybytes(py, &message),
                     ServerLoginState {
                         inner: ServerLoginStateInner::P521Sha512(Some(result.state)),
+                        credential_identifier: Some(credential_identifier.clone()),
                     },
                 ))
             }
-            (ServerSetupInner::MlKem768Ristretto255Sha512(setup), ServerRegistrationInner::MlKem768Ristretto255Sha512(reg)) => {
+            ServerSetupInner::MlKem768Ristretto255Sha512(setup) => {
+                let record = match &password_file {
+                    Some(password_file) => match &password_file.inner {
+                        ServerRegistrationInner::MlKem768Ristretto255Sha512(reg) => {
+                            Some(reg.clone())
+                        }
+                        _ => {
+                            return Err(invalid_state_err(
+                                "ServerSetup and ServerRegistration use different cipher suites",
+                            ));
+                        }
+                    },
+                    None => None,
+                };
                 let request =
                     CredentialRequest::<MlKem768Ristretto255Sha512>::deserialize(&request)
                         .map_err(to_py_err)?;
                 let result = ServerLogin::<MlKem768Ristretto255Sha512>::start(
                     &mut rng,
                     setup,
-                    Some(reg.clone()),
+                    record,
                     request,
                     &credential_identifier,
                     parameters,
@@ -310,22 +450,26 @@ impl OpaqueServer {
                     py_utils::to_pybytes(py, &message),
                     ServerLoginState {
                         inner: ServerLoginStateInner::MlKem768Ristretto255Sha512(Some(result.state)),
+                        credential_identifier: Some(credential_identifier.clone()),
                     },
                 ))
             }
-            _ => Err(invalid_state_err(
-                "ServerSetup and ServerRegistration use different cipher suites",
-            )),
         }
     }
 
+    /// `legacy_bytes`, if set to `True`, returns `session_key` as plain
+    /// `bytes` instead of the default redacting `SecretBytes` — only for
+    /// callers migrating existing code that logs or serializes this value
+    /// directly; new code should leave it unset.
     fn finish_login(
         &self,
         py: Python<'_>,
         mut state: PyRefMut<'_, ServerLoginState>,
         finalization: Vec<u8>,
         params: Option<PyRef<'_, PyServerLoginParameters>>,
-    ) -> PyResult<Py<PyBytes>> {
+        legacy_bytes: Option<bool>,
+    ) -> PyResult<Py<PyAny>> {
+        let legacy_bytes = legacy_bytes.unwrap_or(false);
         if state.suite_id() != self.suite {
             return Err(invalid_state_err(
                 "ServerLoginState does not match this server instance",
@@ -349,15 +493,21 @@ impl OpaqueServer {
         } else {
             ServerLoginParameters::default()
         };
-        match self.suite {
+        let throttle = params.as_ref().and_then(|params| params.throttle());
+        let credential_identifier = state.credential_identifier().map(|id| id.to_vec());
+
+        // Run the actual finish as a closure, not a bare `match` with `?`, so
+        // that a malformed finalization or a reused state also counts as a
+        // throttle failure below — otherwise sending garbage instead of a
+        // wrong password would dodge the lockout entirely.
+        let outcome: PyResult<Vec<u8>> = (|| match self.suite {
             SuiteId::Ristretto255Sha512 => {
                 let state = state.take_ristretto()?;
                 let finalization =
                     CredentialFinalization::<Ristretto255Sha512>::deserialize(&finalization)
                         .map_err(to_py_err)?;
                 let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-                let session_key = result.session_key.to_vec();
-                Ok(py_utils::to_pybytes(py, &session_key))
+                Ok(result.session_key.to_vec())
             }
             SuiteId::P256Sha256 => {
                 let state = state.take_p256()?;
@@ -365,8 +515,7 @@ impl OpaqueServer {
                     CredentialFinalization::<P256Sha256>::deserialize(&finalization)
                         .map_err(to_py_err)?;
                 let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-                let session_key = result.session_key.to_vec();
-                Ok(py_utils::to_pybytes(py, &session_key))
+                Ok(result.session_key.to_vec())
             }
             SuiteId::P384Sha384 => {
                 let state = state.take_p384()?;
@@ -374,8 +523,7 @@ impl OpaqueServer {
                     CredentialFinalization::<P384Sha384>::deserialize(&finalization)
                         .map_err(to_py_err)?;
                 let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-                let session_key = result.session_key.to_vec();
-                Ok(py_utils::to_pybytes(py, &session_key))
+                Ok(result.session_key.to_vec())
             }
             SuiteId::P521Sha512 => {
                 let state = state.take_p521()?;
@@ -383,8 +531,7 @@ impl OpaqueServer {
                     CredentialFinalization::<P521Sha512>::deserialize(&finalization)
                         .map_err(to_py_err)?;
                 let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-                let session_key = result.session_key.to_vec();
-                Ok(py_utils::to_pybytes(py, &session_key))
+                Ok(result.session_key.to_vec())
             }
             SuiteId::MlKem768Ristretto255Sha512 => {
                 let state = state.take_kem()?;
@@ -392,10 +539,22 @@ impl OpaqueServer {
                     CredentialFinalization::<MlKem768Ristretto255Sha512>::deserialize(&finalization)
                         .map_err(to_py_err)?;
                 let result = state.finish(finalization, parameters).map_err(to_py_err)?;
-                let session_key = result.session_key.to_vec();
-                Ok(py_utils::to_pybytes(py, &session_key))
+                Ok(result.session_key.to_vec())
+            }
+        })();
+
+        if let (Some(throttle), Some(credential_identifier)) = (&throttle, &credential_identifier) {
+            match &outcome {
+                Ok(_) => throttle.borrow_mut(py).record_success(credential_identifier.clone()),
+                // A clock callback raising here is itself noteworthy, but it
+                // must not bury the real reason the login failed.
+                Err(_) => {
+                    let _ = throttle.borrow_mut(py).record_failure(py, credential_identifier.clone());
+                }
             }
         }
+
+        py_utils::secret_or_bytes(py, outcome?, legacy_bytes)
     }
 }
 
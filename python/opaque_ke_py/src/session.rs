@@ -0,0 +1,138 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyModule};
+use zeroize::Zeroizing;
+
+use crate::client::OpaqueClient;
+use crate::errors::invalid_state_err;
+use crate::py_utils;
+use crate::types::{
+    ClientLoginFinishParameters as PyClientLoginFinishParameters, ClientLoginState, SecretBytes,
+};
+
+enum SessionState {
+    NotStarted,
+    AwaitingChallenge {
+        state: Py<ClientLoginState>,
+        password: Zeroizing<Vec<u8>>,
+    },
+    Finished,
+}
+
+/// The session_key/export_key/server_s_pk a `ClientAuthSession` surfaces once
+/// `step` completes the exchange.
+#[pyclass(unsendable)]
+pub struct LoginOutcome {
+    session_key: SecretBytes,
+    export_key: SecretBytes,
+    server_s_pk: Py<PyBytes>,
+}
+
+#[pymethods]
+impl LoginOutcome {
+    fn session_key(&self) -> SecretBytes {
+        self.session_key.clone()
+    }
+
+    fn export_key(&self) -> SecretBytes {
+        self.export_key.clone()
+    }
+
+    fn server_s_pk(&self, py: Python<'_>) -> Py<PyBytes> {
+        self.server_s_pk.clone_ref(py)
+    }
+}
+
+/// A drop-in authenticator for message-oriented transports (a socket, a
+/// pair of HTTP requests, a SASL-style line protocol) that wraps
+/// `OpaqueClient`'s login calls so application code never touches the raw
+/// `ClientLoginState`: `begin(password)` issues the first client message,
+/// and `step(response)` consumes the server's challenge and returns the
+/// finalization message to send back alongside the derived keys.
+#[pyclass(unsendable)]
+pub struct ClientAuthSession {
+    client: Py<OpaqueClient>,
+    expected_server_s_pk: Option<Vec<u8>>,
+    state: SessionState,
+}
+
+#[pymethods]
+impl ClientAuthSession {
+    #[new]
+    #[pyo3(signature = (client, expected_server_s_pk=None))]
+    fn new(client: Py<OpaqueClient>, expected_server_s_pk: Option<Vec<u8>>) -> Self {
+        Self {
+            client,
+            expected_server_s_pk,
+            state: SessionState::NotStarted,
+        }
+    }
+
+    /// Issues the client's initial credential request. May only be called
+    /// once per session.
+    fn begin(&mut self, py: Python<'_>, password: Vec<u8>) -> PyResult<Py<PyBytes>> {
+        if !matches!(self.state, SessionState::NotStarted) {
+            return Err(invalid_state_err(
+                "ClientAuthSession.begin() has already been called",
+            ));
+        }
+        let password = Zeroizing::new(password);
+        let client = self.client.bind(py);
+        let result = client.call_method1("start_login", (password.to_vec(),))?;
+        let (request, state): (Py<PyBytes>, Py<ClientLoginState>) = result.extract()?;
+        self.state = SessionState::AwaitingChallenge { state, password };
+        Ok(request)
+    }
+
+    /// Consumes the server's credential response, completing the exchange.
+    /// Returns the finalization message to send back to the server together
+    /// with the session's derived keys. If `expected_server_s_pk` was given
+    /// at construction and doesn't match the server's actual key, this
+    /// raises `InvalidLoginError` instead of returning a result.
+    fn step(&mut self, py: Python<'_>, response: Vec<u8>) -> PyResult<(Py<PyBytes>, LoginOutcome)> {
+        let (state, password) = match std::mem::replace(&mut self.state, SessionState::Finished) {
+            SessionState::AwaitingChallenge { state, password } => (state, password),
+            SessionState::NotStarted => {
+                return Err(invalid_state_err(
+                    "ClientAuthSession.begin() must be called before step()",
+                ));
+            }
+            SessionState::Finished => {
+                return Err(invalid_state_err(
+                    "ClientAuthSession has already completed its exchange",
+                ));
+            }
+        };
+        let finish_params = match &self.expected_server_s_pk {
+            Some(server_s_pk) => Some(Py::new(
+                py,
+                PyClientLoginFinishParameters::with_server_s_pk(server_s_pk.clone()),
+            )?),
+            None => None,
+        };
+        let client = self.client.bind(py);
+        let result = client.call_method1(
+            "finish_login",
+            (state, password.to_vec(), response, finish_params, false),
+        )?;
+        let (message, session_key, export_key, server_s_pk): (
+            Py<PyBytes>,
+            SecretBytes,
+            SecretBytes,
+            Py<PyBytes>,
+        ) = result.extract()?;
+        let outcome = LoginOutcome {
+            session_key,
+            export_key,
+            server_s_pk,
+        };
+        Ok((message, outcome))
+    }
+}
+
+pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let module = py_utils::new_submodule(py, parent, "session")?;
+    module.add_class::<ClientAuthSession>()?;
+    module.add_class::<LoginOutcome>()?;
+    py_utils::add_submodule(py, parent, "session", &module)?;
+    Ok(())
+}
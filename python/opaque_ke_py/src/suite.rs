@@ -1,10 +1,11 @@
-use opaque_ke::argon2::Argon2;
 use opaque_ke::ml_kem::MlKem768;
 use opaque_ke::{CipherSuite, Ristretto255, TripleDh, TripleDhKem};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use sha2::{Sha256, Sha384, Sha512};
 
+use crate::ksf::AnyKsf;
+
 pub(crate) const RISTRETTO255_SHA512: &str = "ristretto255_sha512";
 pub(crate) const P256_SHA256: &str = "p256_sha256";
 pub(crate) const P384_SHA384: &str = "p384_sha384";
@@ -40,6 +41,54 @@ impl SuiteId {
             ML_KEM_768_RISTRETTO255_SHA512,
         ]
     }
+
+    /// Single-byte discriminant used by self-describing serialization
+    /// envelopes so a suite tag can travel alongside the encoded bytes.
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            SuiteId::Ristretto255Sha512 => 0,
+            SuiteId::P256Sha256 => 1,
+            SuiteId::P384Sha384 => 2,
+            SuiteId::P521Sha512 => 3,
+            SuiteId::MlKem768Ristretto255Sha512 => 4,
+        }
+    }
+
+    pub(crate) fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(SuiteId::Ristretto255Sha512),
+            1 => Some(SuiteId::P256Sha256),
+            2 => Some(SuiteId::P384Sha384),
+            3 => Some(SuiteId::P521Sha512),
+            4 => Some(SuiteId::MlKem768Ristretto255Sha512),
+            _ => None,
+        }
+    }
+
+    /// Digest output length, in bytes, of this suite's associated hash
+    /// (Sha256 for P256, Sha384 for P384, Sha512 everywhere else).
+    pub(crate) fn hash_output_len(self) -> usize {
+        match self {
+            SuiteId::P256Sha256 => 32,
+            SuiteId::P384Sha384 => 48,
+            SuiteId::Ristretto255Sha512
+            | SuiteId::P521Sha512
+            | SuiteId::MlKem768Ristretto255Sha512 => 64,
+        }
+    }
+
+    /// Suites ranked strongest-first for mutual negotiation: the
+    /// post-quantum ML-KEM hybrid, then Ristretto255, then the NIST curves
+    /// from P-521 down to P-256.
+    pub(crate) fn preference_order() -> &'static [SuiteId] {
+        &[
+            SuiteId::MlKem768Ristretto255Sha512,
+            SuiteId::Ristretto255Sha512,
+            SuiteId::P521Sha512,
+            SuiteId::P384Sha384,
+            SuiteId::P256Sha256,
+        ]
+    }
 }
 
 impl std::str::FromStr for SuiteId {
@@ -73,7 +122,7 @@ pub(crate) struct Ristretto255Sha512;
 impl CipherSuite for Ristretto255Sha512 {
     type OprfCs = Ristretto255;
     type KeyExchange = TripleDh<Ristretto255, Sha512>;
-    type Ksf = Argon2<'static>;
+    type Ksf = AnyKsf;
 }
 
 pub(crate) struct P256Sha256;
@@ -81,7 +130,7 @@ pub(crate) struct P256Sha256;
 impl CipherSuite for P256Sha256 {
     type OprfCs = p256::NistP256;
     type KeyExchange = TripleDh<p256::NistP256, Sha256>;
-    type Ksf = Argon2<'static>;
+    type Ksf = AnyKsf;
 }
 
 pub(crate) struct P384Sha384;
@@ -89,7 +138,7 @@ pub(crate) struct P384Sha384;
 impl CipherSuite for P384Sha384 {
     type OprfCs = p384::NistP384;
     type KeyExchange = TripleDh<p384::NistP384, Sha384>;
-    type Ksf = Argon2<'static>;
+    type Ksf = AnyKsf;
 }
 
 pub(crate) struct P521Sha512;
@@ -97,7 +146,7 @@ pub(crate) struct P521Sha512;
 impl CipherSuite for P521Sha512 {
     type OprfCs = p521::NistP521;
     type KeyExchange = TripleDh<p521::NistP521, Sha512>;
-    type Ksf = Argon2<'static>;
+    type Ksf = AnyKsf;
 }
 
 pub(crate) struct MlKem768Ristretto255Sha512;
@@ -105,5 +154,5 @@ pub(crate) struct MlKem768Ristretto255Sha512;
 impl CipherSuite for MlKem768Ristretto255Sha512 {
     type OprfCs = Ristretto255;
     type KeyExchange = TripleDhKem<Ristretto255, Sha512, MlKem768>;
-    type Ksf = Argon2<'static>;
+    type Ksf = AnyKsf;
 }
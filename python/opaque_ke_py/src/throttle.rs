@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+use crate::py_utils;
+
+struct ThrottleEntry {
+    remaining: u32,
+    window_start: f64,
+    locked_until: Option<f64>,
+}
+
+/// Per-identifier attempt counter with a refillable window and a lockout once
+/// the budget is exhausted. Guards `ServerLoginParameters`-driven login flows
+/// against offline-style brute forcing through repeated online attempts.
+#[pyclass(unsendable)]
+pub struct LoginThrottle {
+    max_attempts: u32,
+    window_seconds: f64,
+    lockout_seconds: f64,
+    clock: Option<Py<PyAny>>,
+    entries: HashMap<Vec<u8>, ThrottleEntry>,
+}
+
+impl LoginThrottle {
+    fn now(&self, py: Python<'_>) -> PyResult<f64> {
+        match &self.clock {
+            Some(clock) => clock.call0(py)?.extract(py),
+            None => Ok(SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64()),
+        }
+    }
+
+    fn entry_mut(&mut self, identifier: Vec<u8>, now: f64) -> &mut ThrottleEntry {
+        let max_attempts = self.max_attempts;
+        self.entries.entry(identifier).or_insert_with(|| ThrottleEntry {
+            remaining: max_attempts,
+            window_start: now,
+            locked_until: None,
+        })
+    }
+}
+
+#[pymethods]
+impl LoginThrottle {
+    #[new]
+    #[pyo3(signature = (max_attempts, window_seconds, lockout_seconds, clock=None))]
+    fn new(
+        max_attempts: u32,
+        window_seconds: f64,
+        lockout_seconds: f64,
+        clock: Option<Py<PyAny>>,
+    ) -> Self {
+        Self {
+            max_attempts,
+            window_seconds,
+            lockout_seconds,
+            clock,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn register_attempt(&mut self, py: Python<'_>, identifier: Vec<u8>) -> PyResult<bool> {
+        let now = self.now(py)?;
+        let window_seconds = self.window_seconds;
+        let max_attempts = self.max_attempts;
+        let entry = self.entry_mut(identifier, now);
+        if let Some(locked_until) = entry.locked_until {
+            if now < locked_until {
+                return Ok(false);
+            }
+            entry.locked_until = None;
+            entry.remaining = max_attempts;
+            entry.window_start = now;
+        } else if now - entry.window_start >= window_seconds {
+            entry.remaining = max_attempts;
+            entry.window_start = now;
+        }
+        Ok(entry.remaining > 0)
+    }
+
+    fn record_failure(&mut self, py: Python<'_>, identifier: Vec<u8>) -> PyResult<()> {
+        let now = self.now(py)?;
+        let window_seconds = self.window_seconds;
+        let lockout_seconds = self.lockout_seconds;
+        let max_attempts = self.max_attempts;
+        let entry = self.entry_mut(identifier, now);
+        if now - entry.window_start >= window_seconds {
+            entry.remaining = max_attempts;
+            entry.window_start = now;
+        }
+        entry.remaining = entry.remaining.saturating_sub(1);
+        if entry.remaining == 0 {
+            entry.locked_until = Some(now + lockout_seconds);
+        }
+        Ok(())
+    }
+
+    fn record_success(&mut self, identifier: Vec<u8>) {
+        self.entries.remove(&identifier);
+    }
+}
+
+pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let module = py_utils::new_submodule(py, parent, "throttle")?;
+    module.add_class::<LoginThrottle>()?;
+    py_utils::add_submodule(py, parent, "throttle", &module)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_out_after_max_attempts_then_resets_on_success() {
+        Python::with_gil(|py| {
+            let mut throttle = LoginThrottle::new(2, 1_000.0, 1_000.0, None);
+            let id = b"alice".to_vec();
+
+            assert!(throttle.register_attempt(py, id.clone()).unwrap());
+            throttle.record_failure(py, id.clone()).unwrap();
+            assert!(throttle.register_attempt(py, id.clone()).unwrap());
+            throttle.record_failure(py, id.clone()).unwrap();
+
+            // Budget exhausted: locked out even though the window hasn't
+            // elapsed yet.
+            assert!(!throttle.register_attempt(py, id.clone()).unwrap());
+
+            throttle.record_success(id.clone());
+
+            // A fresh entry gets a fresh budget.
+            assert!(throttle.register_attempt(py, id).unwrap());
+        });
+    }
+
+    #[test]
+    fn refills_once_the_window_elapses() {
+        Python::with_gil(|py| {
+            // window_seconds=0.0 means the window has always already
+            // elapsed, so a failure that doesn't exhaust the budget still
+            // gets a fresh window (and fresh remaining count) on the next
+            // attempt rather than carrying its decremented count forward.
+            // Without the refill, the second failure below would use up the
+            // budget of 2 and lock the identifier out.
+            let mut throttle = LoginThrottle::new(2, 0.0, 1_000.0, None);
+            let id = b"bob".to_vec();
+
+            assert!(throttle.register_attempt(py, id.clone()).unwrap());
+            throttle.record_failure(py, id.clone()).unwrap();
+            assert!(throttle.register_attempt(py, id.clone()).unwrap());
+            throttle.record_failure(py, id.clone()).unwrap();
+            assert!(throttle.register_attempt(py, id).unwrap());
+        });
+    }
+}
@@ -1,19 +1,63 @@
 use opaque_ke::argon2::{Algorithm, Argon2, Params, Version};
-use opaque_ke::rand::rngs::OsRng;
+use opaque_ke::keypair::KeyPair;
 use opaque_ke::{
-    ClientLogin, ClientRegistration, Identifiers as OpaqueIdentifiers, ServerLogin,
+    ClientLogin, ClientRegistration, Identifiers as OpaqueIdentifiers, Ristretto255, ServerLogin,
     ServerRegistration as OpaqueServerRegistration, ServerSetup as OpaqueServerSetup,
 };
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyModule};
+use zeroize::Zeroizing;
 
 use crate::errors::{invalid_state_err, to_py_err};
+use crate::ksf::{AnyKsf, Pbkdf2Hash};
 use crate::py_utils;
+use crate::rng::make_rng;
 use crate::suite::{
     MlKem768Ristretto255Sha512, P256Sha256, P384Sha384, P521Sha512, Ristretto255Sha512, SuiteId,
     parse_suite,
 };
+use crate::throttle::LoginThrottle;
+
+/// Holds sensitive key material (e.g. `export_key`) so it can't land in a
+/// log line or traceback by accident. The real bytes are only reachable
+/// through an explicit [`SecretBytes::reveal`] call.
+#[pyclass(unsendable)]
+#[derive(Clone)]
+pub struct SecretBytes {
+    data: Zeroizing<Vec<u8>>,
+}
+
+impl SecretBytes {
+    pub(crate) fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: Zeroizing::new(data),
+        }
+    }
+}
+
+#[pymethods]
+impl SecretBytes {
+    fn reveal(&self, py: Python<'_>) -> Py<PyBytes> {
+        py_utils::to_pybytes(py, &self.data)
+    }
+
+    fn expose_sensitive(&self, py: Python<'_>) -> Py<PyBytes> {
+        self.reveal(py)
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SecretBytes(len={}, <redacted>)", self.data.len())
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+}
 
 #[pyclass(unsendable)]
 #[derive(Clone)]
@@ -41,6 +85,9 @@ impl Identifiers {
     }
 }
 
+/// Tunable Argon2id cost parameters for the `argon2id` key-stretching
+/// variant, so a deployment can match memory-hardness to its own threat
+/// model instead of relying on the built-in default.
 #[pyclass(unsendable)]
 #[derive(Clone)]
 pub struct Argon2Params {
@@ -62,13 +109,17 @@ impl Argon2Params {
             self.parallelism,
             self.output_length,
         )
-        .map_err(|err| PyErr::new::<PyValueError, _>(err.to_string()))
+        .map_err(to_py_err)
     }
 }
 
 #[pymethods]
 impl Argon2Params {
+    /// Defaults match the OWASP-recommended interactive argon2id profile
+    /// (19 MiB, 2 iterations, 1 degree of parallelism) so a caller who just
+    /// wants "a reasonable cost" can write `Argon2Params()`.
     #[new]
+    #[pyo3(signature = (memory_cost_kib=19456, time_cost=2, parallelism=1, output_length=None))]
     fn new(
         memory_cost_kib: u32,
         time_cost: u32,
@@ -84,46 +135,156 @@ impl Argon2Params {
     }
 }
 
+#[pyclass(unsendable)]
+#[derive(Clone)]
+pub struct ScryptParams {
+    #[pyo3(get)]
+    log_n: u8,
+    #[pyo3(get)]
+    r: u32,
+    #[pyo3(get)]
+    p: u32,
+}
+
+#[pymethods]
+impl ScryptParams {
+    #[new]
+    fn new(log_n: u8, r: u32, p: u32) -> Self {
+        Self { log_n, r, p }
+    }
+}
+
+#[pyclass(unsendable)]
+#[derive(Clone)]
+pub struct Pbkdf2Params {
+    #[pyo3(get)]
+    hash: String,
+    #[pyo3(get)]
+    iterations: u32,
+}
+
+impl Pbkdf2Params {
+    fn to_hash(&self) -> PyResult<Pbkdf2Hash> {
+        match self.hash.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Pbkdf2Hash::Sha256),
+            "sha512" => Ok(Pbkdf2Hash::Sha512),
+            other => Err(PyErr::new::<PyValueError, _>(format!(
+                "unsupported pbkdf2 hash '{other}' (available: sha256, sha512)"
+            ))),
+        }
+    }
+}
+
+#[pymethods]
+impl Pbkdf2Params {
+    #[new]
+    fn new(hash: &str, iterations: u32) -> Self {
+        Self {
+            hash: hash.to_ascii_lowercase(),
+            iterations,
+        }
+    }
+}
+
+/// Selects the key-stretching function opaque-ke runs over the OPRF output
+/// before it's used to open the envelope. The registration call that created
+/// a password file and every later login finish against it must agree on
+/// this choice (variant and params alike) or the derived keys won't match —
+/// opaque-ke has no way to carry that choice in the wire format itself, so
+/// it's on the caller to keep the two calls in sync.
 #[pyclass(unsendable)]
 #[derive(Clone)]
 pub struct KeyStretching {
     #[pyo3(get)]
     variant: String,
-    params: Option<Argon2Params>,
+    argon2_params: Option<Argon2Params>,
+    scrypt_params: Option<ScryptParams>,
+    pbkdf2_params: Option<Pbkdf2Params>,
 }
 
 impl KeyStretching {
-    pub(crate) fn build_ksf(&self) -> PyResult<Argon2<'static>> {
-        let params = if let Some(params) = self.params.as_ref() {
-            params.to_params()?
-        } else {
-            match self.variant.as_str() {
-                "memory_constrained" => Params::new(1 << 16, 3, 4, None)
-                    .map_err(|err| PyErr::new::<PyValueError, _>(err.to_string()))?,
-                "rfc_recommended" => Params::new((1 << 21) - 1, 1, 4, None)
-                    .map_err(|err| PyErr::new::<PyValueError, _>(err.to_string()))?,
-                _ => Params::DEFAULT,
+    pub(crate) fn build_ksf(&self) -> PyResult<AnyKsf> {
+        match self.variant.as_str() {
+            "argon2id" | "memory_constrained" | "rfc_recommended" => {
+                let params = if let Some(params) = self.argon2_params.as_ref() {
+                    params.to_params()?
+                } else {
+                    match self.variant.as_str() {
+                        "memory_constrained" => {
+                            Params::new(1 << 16, 3, 4, None).map_err(to_py_err)?
+                        }
+                        "rfc_recommended" => {
+                            Params::new((1 << 21) - 1, 1, 4, None).map_err(to_py_err)?
+                        }
+                        _ => Params::DEFAULT,
+                    }
+                };
+                Ok(AnyKsf::Argon2(Argon2::new(
+                    Algorithm::Argon2id,
+                    Version::V0x13,
+                    params,
+                )))
             }
-        };
-        let algorithm = Algorithm::Argon2id;
-        let version = Version::V0x13;
-        Ok(Argon2::new(algorithm, version, params))
+            "scrypt" => {
+                let params = self.scrypt_params.as_ref().ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(
+                        "key stretching variant 'scrypt' requires scrypt_params",
+                    )
+                })?;
+                Ok(AnyKsf::Scrypt {
+                    log_n: params.log_n,
+                    r: params.r,
+                    p: params.p,
+                })
+            }
+            "pbkdf2" => {
+                let params = self.pbkdf2_params.as_ref().ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(
+                        "key stretching variant 'pbkdf2' requires pbkdf2_params",
+                    )
+                })?;
+                Ok(AnyKsf::Pbkdf2 {
+                    hash: params.to_hash()?,
+                    iterations: params.iterations,
+                })
+            }
+            "identity" => Ok(AnyKsf::Identity),
+            other => Err(PyErr::new::<PyValueError, _>(format!(
+                "unsupported key stretching variant '{other}'"
+            ))),
+        }
     }
 }
 
 #[pymethods]
 impl KeyStretching {
     #[new]
-    fn new(variant: &str, params: Option<PyRef<'_, Argon2Params>>) -> PyResult<Self> {
+    #[pyo3(signature = (variant, argon2_params=None, scrypt_params=None, pbkdf2_params=None))]
+    fn new(
+        variant: &str,
+        argon2_params: Option<PyRef<'_, Argon2Params>>,
+        scrypt_params: Option<PyRef<'_, ScryptParams>>,
+        pbkdf2_params: Option<PyRef<'_, Pbkdf2Params>>,
+    ) -> PyResult<Self> {
         let normalized = variant.to_ascii_lowercase();
-        if normalized != "memory_constrained" && normalized != "rfc_recommended" {
+        const VALID_VARIANTS: [&str; 6] = [
+            "argon2id",
+            "memory_constrained",
+            "rfc_recommended",
+            "scrypt",
+            "pbkdf2",
+            "identity",
+        ];
+        if !VALID_VARIANTS.contains(&normalized.as_str()) {
             return Err(PyErr::new::<PyValueError, _>(format!(
                 "unsupported key stretching variant '{normalized}'"
             )));
         }
         Ok(Self {
             variant: normalized,
-            params: params.map(|value| value.clone()),
+            argon2_params: argon2_params.map(|value| value.clone()),
+            scrypt_params: scrypt_params.map(|value| value.clone()),
+            pbkdf2_params: pbkdf2_params.map(|value| value.clone()),
         })
     }
 }
@@ -164,6 +325,7 @@ impl ClientRegistrationFinishParameters {
 pub struct ServerLoginParameters {
     context: Option<Vec<u8>>,
     identifiers: Option<Identifiers>,
+    throttle: Option<Py<LoginThrottle>>,
 }
 
 impl ServerLoginParameters {
@@ -174,15 +336,35 @@ impl ServerLoginParameters {
     pub(crate) fn identifiers(&self) -> Option<&Identifiers> {
         self.identifiers.as_ref()
     }
+
+    pub(crate) fn throttle(&self) -> Option<&Py<LoginThrottle>> {
+        self.throttle.as_ref()
+    }
+
+    /// Builds a `ServerLoginParameters` carrying only a `context`, for
+    /// callers (like the `sasl` driver) assembling one outside of Python.
+    pub(crate) fn with_context(context: Vec<u8>) -> Self {
+        Self {
+            context: Some(context),
+            identifiers: None,
+            throttle: None,
+        }
+    }
 }
 
 #[pymethods]
 impl ServerLoginParameters {
     #[new]
-    fn new(context: Option<Vec<u8>>, identifiers: Option<PyRef<'_, Identifiers>>) -> Self {
+    #[pyo3(signature = (context=None, identifiers=None, throttle=None))]
+    fn new(
+        context: Option<Vec<u8>>,
+        identifiers: Option<PyRef<'_, Identifiers>>,
+        throttle: Option<Py<LoginThrottle>>,
+    ) -> Self {
         Self {
             context,
             identifiers: identifiers.map(|value| value.clone()),
+            throttle,
         }
     }
 }
@@ -197,6 +379,19 @@ pub struct ClientLoginFinishParameters {
 }
 
 impl ClientLoginFinishParameters {
+    /// Builds a parameter set that only pins the expected server public key,
+    /// leaving context/identifiers/key-stretching at their defaults. Used by
+    /// callers (like `ClientAuthSession`) that construct finish parameters
+    /// from Rust rather than accepting one from Python.
+    pub(crate) fn with_server_s_pk(server_s_pk: Vec<u8>) -> Self {
+        Self {
+            context: None,
+            identifiers: None,
+            key_stretching: None,
+            server_s_pk: Some(server_s_pk),
+        }
+    }
+
     pub(crate) fn context(&self) -> Option<&[u8]> {
         self.context.as_deref()
     }
@@ -260,9 +455,10 @@ pub struct ServerSetup {
 #[pymethods]
 impl ServerSetup {
     #[new]
-    fn new(suite: Option<&str>) -> PyResult<Self> {
+    #[pyo3(signature = (suite=None, seed=None))]
+    fn new(suite: Option<&str>, seed: Option<Vec<u8>>) -> PyResult<Self> {
         let suite = parse_suite(suite)?;
-        let mut rng = OsRng;
+        let mut rng = make_rng(seed.as_deref(), None)?;
         let inner = match suite {
             SuiteId::Ristretto255Sha512 => ServerSetupInner::Ristretto255Sha512(
                 OpaqueServerSetup::<Ristretto255Sha512>::new(&mut rng),
@@ -285,6 +481,58 @@ impl ServerSetup {
         Ok(Self { inner })
     }
 
+    /// Builds a `ServerSetup` from an externally managed server private key
+    /// (e.g. held in a KMS/HSM), rather than generating one internally.
+    /// `seed`, if given, deterministically drives the OPRF seed generation
+    /// the same way `ServerSetup(seed=...)` does, so the whole setup is
+    /// reproducible across restarts and replicas. The key length is
+    /// validated against the chosen suite's key-exchange group.
+    #[staticmethod]
+    #[pyo3(signature = (private_key, suite=None, seed=None))]
+    fn from_key_pair(private_key: Vec<u8>, suite: Option<&str>, seed: Option<Vec<u8>>) -> PyResult<Self> {
+        let suite = parse_suite(suite)?;
+        let mut rng = make_rng(seed.as_deref(), None)?;
+        let inner = match suite {
+            SuiteId::Ristretto255Sha512 => {
+                let keypair = KeyPair::<Ristretto255>::from_private_key_slice(&private_key)
+                    .map_err(|_| invalid_state_err("private key is not valid for Ristretto255Sha512"))?;
+                ServerSetupInner::Ristretto255Sha512(OpaqueServerSetup::<Ristretto255Sha512>::new_with_key_pair(
+                    &mut rng, keypair,
+                ))
+            }
+            SuiteId::P256Sha256 => {
+                let keypair = KeyPair::<p256::NistP256>::from_private_key_slice(&private_key)
+                    .map_err(|_| invalid_state_err("private key is not valid for P256Sha256"))?;
+                ServerSetupInner::P256Sha256(OpaqueServerSetup::<P256Sha256>::new_with_key_pair(
+                    &mut rng, keypair,
+                ))
+            }
+            SuiteId::P384Sha384 => {
+                let keypair = KeyPair::<p384::NistP384>::from_private_key_slice(&private_key)
+                    .map_err(|_| invalid_state_err("private key is not valid for P384Sha384"))?;
+                ServerSetupInner::P384Sha384(OpaqueServerSetup::<P384Sha384>::new_with_key_pair(
+                    &mut rng, keypair,
+                ))
+            }
+            SuiteId::P521Sha512 => {
+                let keypair = KeyPair::<p521::NistP521>::from_private_key_slice(&private_key)
+                    .map_err(|_| invalid_state_err("private key is not valid for P521Sha512"))?;
+                ServerSetupInner::P521Sha512(OpaqueServerSetup::<P521Sha512>::new_with_key_pair(
+                    &mut rng, keypair,
+                ))
+            }
+            SuiteId::MlKem768Ristretto255Sha512 => {
+                let keypair = KeyPair::<Ristretto255>::from_private_key_slice(&private_key).map_err(|_| {
+                    invalid_state_err("private key is not valid for MlKem768Ristretto255Sha512")
+                })?;
+                ServerSetupInner::MlKem768Ristretto255Sha512(OpaqueServerSetup::<
+                    MlKem768Ristretto255Sha512,
+                >::new_with_key_pair(&mut rng, keypair))
+            }
+        };
+        Ok(Self { inner })
+    }
+
     #[staticmethod]
     #[pyo3(signature = (data, suite=None))]
     fn deserialize(data: Vec<u8>, suite: Option<&str>) -> PyResult<Self> {
@@ -320,6 +568,18 @@ impl ServerSetup {
         };
         py_utils::to_pybytes(py, &serialized)
     }
+
+    fn serialize_tagged(&self, py: Python<'_>) -> Py<PyBytes> {
+        let raw = self.serialize(py);
+        let payload = py_utils::encode_tagged(self.suite_id(), raw.bind(py).as_bytes());
+        py_utils::to_pybytes(py, &payload)
+    }
+
+    #[staticmethod]
+    fn deserialize_tagged(data: Vec<u8>) -> PyResult<Self> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        Self::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
 }
 
 impl ServerSetup {
@@ -397,6 +657,25 @@ impl ServerRegistration {
         };
         Ok(py_utils::to_pybytes(py, &serialized))
     }
+
+    fn serialize_tagged(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
+        let raw = self.serialize(py)?;
+        let payload = py_utils::encode_tagged(self.suite_id(), raw.bind(py).as_bytes());
+        Ok(py_utils::to_pybytes(py, &payload))
+    }
+
+    #[staticmethod]
+    fn deserialize_tagged(data: Vec<u8>) -> PyResult<Self> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        Self::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ServerRegistration(suite={}, <redacted>)",
+            self.suite_id().as_str()
+        )
+    }
 }
 
 impl ServerRegistration {
@@ -491,6 +770,35 @@ impl ClientRegistrationState {
         };
         Ok(py_utils::to_pybytes(py, &serialized))
     }
+
+    fn serialize_tagged(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
+        let raw = self.serialize(py)?;
+        let payload = py_utils::encode_tagged(self.suite_id(), raw.bind(py).as_bytes());
+        Ok(py_utils::to_pybytes(py, &payload))
+    }
+
+    #[staticmethod]
+    fn deserialize_tagged(data: Vec<u8>) -> PyResult<Self> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        Self::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    /// Lets `pickle` (and anything else that follows the reduce protocol,
+    /// like a session-store client) stash this state between the `start_*`
+    /// and `finish_*` calls of a stateless web request without callers
+    /// having to call `serialize_tagged`/`deserialize_tagged` by hand.
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(Py<PyAny>, (Py<PyBytes>,))> {
+        let data = self.serialize_tagged(py)?;
+        let ctor = py.get_type::<Self>().getattr("deserialize_tagged")?.into();
+        Ok((ctor, (data,)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ClientRegistrationState(suite={}, <redacted>)",
+            self.suite_id().as_str()
+        )
+    }
 }
 
 impl ClientRegistrationState {
@@ -640,6 +948,25 @@ impl ClientLoginState {
         };
         Ok(py_utils::to_pybytes(py, &serialized))
     }
+
+    fn serialize_tagged(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
+        let raw = self.serialize(py)?;
+        let payload = py_utils::encode_tagged(self.suite_id(), raw.bind(py).as_bytes());
+        Ok(py_utils::to_pybytes(py, &payload))
+    }
+
+    #[staticmethod]
+    fn deserialize_tagged(data: Vec<u8>) -> PyResult<Self> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        Self::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    /// See `ClientRegistrationState::__reduce__`.
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(Py<PyAny>, (Py<PyBytes>,))> {
+        let data = self.serialize_tagged(py)?;
+        let ctor = py.get_type::<Self>().getattr("deserialize_tagged")?.into();
+        Ok((ctor, (data,)))
+    }
 }
 
 impl ClientLoginState {
@@ -728,6 +1055,13 @@ impl ServerLoginStateInner {
 #[pyclass(unsendable)]
 pub struct ServerLoginState {
     pub(crate) inner: ServerLoginStateInner,
+    /// The `credential_identifier` this state was started with, so
+    /// `finish_login` can report the outcome to a `LoginThrottle` without
+    /// making the caller pass it a second time. Not preserved across
+    /// `serialize`/`deserialize`, since it isn't part of opaque-ke's own
+    /// wire format — a state resumed from bytes on another process simply
+    /// skips the throttle bookkeeping.
+    pub(crate) credential_identifier: Option<Vec<u8>>,
 }
 
 #[pymethods]
@@ -756,7 +1090,10 @@ impl ServerLoginState {
                 ))
             }
         };
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            credential_identifier: None,
+        })
     }
 
     fn serialize(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
@@ -789,6 +1126,28 @@ impl ServerLoginState {
         };
         Ok(py_utils::to_pybytes(py, &serialized))
     }
+
+    fn serialize_tagged(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
+        let raw = self.serialize(py)?;
+        let payload = py_utils::encode_tagged(self.suite_id(), raw.bind(py).as_bytes());
+        Ok(py_utils::to_pybytes(py, &payload))
+    }
+
+    #[staticmethod]
+    fn deserialize_tagged(data: Vec<u8>) -> PyResult<Self> {
+        let (suite, payload) = py_utils::decode_tagged(&data)?;
+        Self::deserialize(payload.to_vec(), Some(suite.as_str()))
+    }
+
+    /// See `ClientRegistrationState::__reduce__`. Lets a load-balanced
+    /// server stash login state (in a cookie, Redis, or a database row)
+    /// between `start_login` and `finish_login` instead of pinning the
+    /// request to one process.
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(Py<PyAny>, (Py<PyBytes>,))> {
+        let data = self.serialize_tagged(py)?;
+        let ctor = py.get_type::<Self>().getattr("deserialize_tagged")?.into();
+        Ok((ctor, (data,)))
+    }
 }
 
 impl ServerLoginState {
@@ -796,6 +1155,10 @@ impl ServerLoginState {
         self.inner.suite_id()
     }
 
+    pub(crate) fn credential_identifier(&self) -> Option<&[u8]> {
+        self.credential_identifier.as_deref()
+    }
+
     pub(crate) fn take_ristretto(&mut self) -> PyResult<ServerLogin<Ristretto255Sha512>> {
         match &mut self.inner {
             ServerLoginStateInner::Ristretto255Sha512(inner) => inner
@@ -854,8 +1217,11 @@ impl ServerLoginState {
 
 pub fn register(py: Python<'_>, parent: &Bound<'_, PyModule>) -> PyResult<()> {
     let module = py_utils::new_submodule(py, parent, "types")?;
+    module.add_class::<SecretBytes>()?;
     module.add_class::<Identifiers>()?;
     module.add_class::<Argon2Params>()?;
+    module.add_class::<ScryptParams>()?;
+    module.add_class::<Pbkdf2Params>()?;
     module.add_class::<KeyStretching>()?;
     module.add_class::<ClientRegistrationFinishParameters>()?;
     module.add_class::<ServerLoginParameters>()?;